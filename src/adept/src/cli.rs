@@ -0,0 +1,180 @@
+use request::{BuildOptions, ProfileName};
+use std::path::{Path, PathBuf};
+
+pub const USAGE: &str = "\
+usage: adept [OPTIONS] <COMMAND>
+
+commands:
+  build [PATH]                Compile a file or project directory
+  check [PATH]                Check a file or project directory for errors
+  run [PATH]                  Compile and run a file or project directory
+  watch [PATH]                Check for errors again whenever a source file changes
+  eval FILE                   Evaluate a file using the kernel
+  dump <tokens|tree> FILE     Print the tokens or syntax tree of a file
+  repl                        Start an interactive session
+  daemon <start|stop|status>  Manage the background compilation daemon
+  lsp                         Start the language server
+
+options:
+  --infrastructure DIR        Use the infrastructure folder at DIR
+  --project DIR               Use the project in DIR instead of the current directory
+  --profile NAME              Build with the `debug` or `release` profile
+  --release                   Shorthand for `--profile release`
+  --target TRIPLE             Build for the target TRIPLE
+  --no-daemon                 Compile in-process without using the daemon
+  --format <human|json>       Choose the output format for diagnostics
+  -h, --help                  Show this message
+
+If PATH is given instead of a command, `adept PATH` is the same as `adept build PATH`.";
+
+#[derive(Clone, Debug)]
+pub struct Cli {
+    pub command: Command,
+    pub infrastructure: Option<PathBuf>,
+    pub options: BuildOptions,
+    pub no_daemon: bool,
+    pub format: Format,
+}
+
+#[derive(Clone, Debug)]
+pub enum Command {
+    Build(PathBuf),
+    Check(PathBuf),
+    Run(PathBuf),
+    Watch(PathBuf),
+    Eval(PathBuf),
+    Dump(DumpKind, PathBuf),
+    Daemon(DaemonCommand),
+    StartDaemon,
+    Lsp,
+    Repl,
+    Help,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum DaemonCommand {
+    Start,
+    Stop,
+    Status,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum DumpKind {
+    Tokens,
+    Tree,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Human,
+    Json,
+}
+
+impl Cli {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut args = args.into_iter();
+        let mut positional = Vec::new();
+        let mut infrastructure = None;
+        let mut project = None;
+        let mut options = BuildOptions::default();
+        let mut no_daemon = false;
+        let mut format = Format::default();
+        let mut help = false;
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Expected value after `{arg}`"))
+            };
+
+            match arg.as_str() {
+                "-h" | "--help" => help = true,
+                "--infrastructure" => infrastructure = Some(value()?.into()),
+                "--project" => project = Some(value()?.into()),
+                "--profile" => {
+                    let name = value()?;
+                    options.profile = Some(ProfileName::parse(&name).map_err(|_| {
+                        format!("Unknown profile `{name}`, expected `debug` or `release`")
+                    })?);
+                }
+                "--release" => options.profile = Some(ProfileName::Release),
+                "--target" => options.target = Some(value()?.into()),
+                "--no-daemon" => no_daemon = true,
+                "--format" => {
+                    format = match value()?.as_str() {
+                        "human" => Format::Human,
+                        "json" => Format::Json,
+                        other => return Err(format!("Unknown format `{other}`")),
+                    }
+                }
+                // Kept for compatibility with the editor extension and daemon spawning
+                "--daemon" => positional.push("--daemon".into()),
+                "--language-server" => positional.push("lsp".into()),
+                _ if arg.starts_with('-') => return Err(format!("Unknown option `{arg}`")),
+                _ => positional.push(arg),
+            }
+        }
+
+        let mut positional = positional.into_iter();
+        let default_path = || project.clone().unwrap_or_else(|| PathBuf::from("."));
+
+        let command = match positional.next().as_deref() {
+            _ if help => Command::Help,
+            None => Command::Help,
+            Some("--daemon") => Command::StartDaemon,
+            Some("build") => {
+                Command::Build(positional.next().map_or_else(default_path, Into::into))
+            }
+            Some("check") => {
+                Command::Check(positional.next().map_or_else(default_path, Into::into))
+            }
+            Some("run") => Command::Run(positional.next().map_or_else(default_path, Into::into)),
+            Some("watch") => {
+                Command::Watch(positional.next().map_or_else(default_path, Into::into))
+            }
+            Some("eval") => Command::Eval(
+                positional
+                    .next()
+                    .ok_or("Expected filename to evaluate")?
+                    .into(),
+            ),
+            Some("dump") => {
+                let kind = match positional.next().as_deref() {
+                    Some("tokens") => DumpKind::Tokens,
+                    Some("tree") => DumpKind::Tree,
+                    Some(other) => return Err(format!("Unknown dump kind `{other}`")),
+                    None => return Err("Expected `tokens` or `tree`".into()),
+                };
+                Command::Dump(
+                    kind,
+                    positional.next().ok_or("Expected filename to dump")?.into(),
+                )
+            }
+            Some("daemon") => Command::Daemon(match positional.next().as_deref() {
+                Some("start") => DaemonCommand::Start,
+                Some("stop") => DaemonCommand::Stop,
+                Some("status") => DaemonCommand::Status,
+                Some(other) => return Err(format!("Unknown daemon command `{other}`")),
+                None => return Err("Expected `start`, `stop` or `status`".into()),
+            }),
+            Some("lsp") => Command::Lsp,
+            Some("repl") => Command::Repl,
+            // Kept for compatibility with the test runner, which passes only a path
+            Some(path) if Path::new(path).exists() => Command::Build(path.into()),
+            Some(other) => return Err(format!("Unknown command `{other}`")),
+        };
+
+        if let Some(extra) = positional.next() {
+            return Err(format!("Unexpected argument `{extra}`"));
+        }
+
+        Ok(Self {
+            command,
+            infrastructure,
+            options,
+            no_daemon,
+            format,
+        })
+    }
+}
@@ -0,0 +1,257 @@
+use request::{Error, SourceLocation};
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    io::IsTerminal,
+    path::{Path, PathBuf},
+};
+
+/// Version of the JSON diagnostics format, incremented on breaking changes.
+///
+/// Each diagnostic is one line containing an object with `version`, `severity`,
+/// `code` (e.g. `"E0012"`, or `null`), `message`, `file` (absolute path, or `null`),
+/// `range` (`null`, or `utf16` line/character positions and `bytes` file offsets,
+/// all zero-based with exclusive ends) and `notes` (a list of `{ "message": ... }`).
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders errors for humans, including the offending source line when known
+pub struct Renderer {
+    color: bool,
+    files: HashMap<PathBuf, Option<String>>,
+}
+
+impl Renderer {
+    pub fn new() -> Self {
+        Self {
+            color: std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+            files: HashMap::new(),
+        }
+    }
+
+    pub fn render(&mut self, error: &Error) -> String {
+        let message = error.without_location();
+        let mut output = if error.is_warning() {
+            self.render_heading("warning", YELLOW, &message.to_string())
+        } else {
+            self.render_message(&message.to_string())
+        };
+
+        if let Some(location) = error.location() {
+            self.render_snippet(&mut output, location);
+        }
+
+        if let Some(note) = note(message) {
+            let _ = writeln!(
+                output,
+                "  {}= note:{} {}",
+                self.paint(BLUE),
+                self.paint(RESET),
+                note
+            );
+        }
+
+        output.push('\n');
+        output
+    }
+
+    /// Renders a failure that isn't associated with an error from the compiler
+    pub fn render_message(&self, message: &str) -> String {
+        self.render_heading("error", RED, message)
+    }
+
+    fn render_heading(&self, severity: &str, color: &'static str, message: &str) -> String {
+        format!(
+            "{}{severity}{}{}: {}{}\n",
+            self.paint(color),
+            self.paint(RESET),
+            self.paint(BOLD),
+            message,
+            self.paint(RESET),
+        )
+    }
+
+    pub fn render_message_json(&self, message: &str) -> String {
+        serde_json::json!({
+            "version": JSON_SCHEMA_VERSION,
+            "severity": "error",
+            "code": null,
+            "message": message,
+            "file": null,
+            "range": null,
+            "notes": [],
+        })
+        .to_string()
+    }
+
+    /// Renders an error as a single line of JSON. See `JSON_SCHEMA_VERSION`
+    pub fn render_json(&mut self, error: &Error) -> String {
+        let message = error.without_location();
+
+        let (file, range) = match error.location() {
+            Some(location) => {
+                let filename: &Path = location.filename.as_ref();
+                let content = self.content(filename);
+                let byte_offset = |line, col| {
+                    content
+                        .as_deref()
+                        .and_then(|content| byte_offset(content, line, col))
+                };
+
+                let bytes = byte_offset(location.start_line, location.start_col)
+                    .zip(byte_offset(location.end_line, location.end_col))
+                    .map(|(start, end)| serde_json::json!({ "start": start, "end": end }));
+
+                (
+                    Some(filename.to_string_lossy().to_string()),
+                    Some(serde_json::json!({
+                        "utf16": {
+                            "start": { "line": location.start_line, "character": location.start_col },
+                            "end": { "line": location.end_line, "character": location.end_col },
+                        },
+                        "bytes": bytes,
+                    })),
+                )
+            }
+            None => (None, None),
+        };
+
+        let notes = note(message)
+            .map(|note| vec![serde_json::json!({ "message": note })])
+            .unwrap_or_default();
+
+        serde_json::json!({
+            "version": JSON_SCHEMA_VERSION,
+            "severity": if error.is_warning() { "warning" } else { "error" },
+            "code": message.code(),
+            "message": message.to_string(),
+            "file": file,
+            "range": range,
+            "notes": notes,
+        })
+        .to_string()
+    }
+
+    fn content(&mut self, filename: &Path) -> Option<String> {
+        self.files
+            .entry(filename.to_path_buf())
+            .or_insert_with(|| std::fs::read_to_string(filename).ok())
+            .clone()
+    }
+
+    fn render_snippet(&mut self, output: &mut String, location: &SourceLocation) {
+        let filename: &Path = location.filename.as_ref();
+        let line_number = location.start_line + 1;
+        let gutter = " ".repeat(line_number.to_string().len());
+        let (blue, reset) = (self.paint(BLUE), self.paint(RESET));
+
+        let line = self
+            .content(filename)
+            .and_then(|content| content.lines().nth(location.start_line).map(str::to_string));
+
+        let Some(line) = line else {
+            let _ = writeln!(
+                output,
+                "{gutter}{blue}-->{reset} {}:{}",
+                display_path(filename),
+                line_number,
+            );
+            return;
+        };
+
+        let start = char_index(&line, location.start_col);
+        let end = if location.end_line == location.start_line {
+            char_index(&line, location.end_col)
+        } else {
+            line.chars().count()
+        };
+
+        let _ = writeln!(
+            output,
+            "{gutter}{blue}-->{reset} {}:{}:{}",
+            display_path(filename),
+            line_number,
+            start + 1,
+        );
+        let _ = writeln!(output, "{gutter} {blue}|{reset}");
+        let _ = writeln!(output, "{blue}{line_number} |{reset} {line}");
+        let _ = writeln!(
+            output,
+            "{gutter} {blue}|{reset} {}{}{}{}",
+            " ".repeat(start),
+            self.paint(RED),
+            "^".repeat(end.saturating_sub(start).max(1)),
+            reset,
+        );
+    }
+
+    fn paint(&self, code: &'static str) -> &'static str {
+        if self.color { code } else { "" }
+    }
+}
+
+fn note(error: &Error) -> Option<&'static str> {
+    match error {
+        Error::UndeclaredName(_) => {
+            Some("names must be declared in the same file, an imported file, or a dependency")
+        }
+        Error::CyclicDependency(_) => {
+            Some("packages in `dependencies` cannot depend on each other")
+        }
+        Error::FailedToResolveImport(_) => Some("imports are relative to the importing file"),
+        Error::FailedToResolveDependency(_) => {
+            Some("dependency paths are relative to the `adept.build` declaring them")
+        }
+        _ => None,
+    }
+}
+
+/// Converts a zero-based line and UTF-16 column into a byte offset within the file
+fn byte_offset(content: &str, line: usize, utf16_col: usize) -> Option<usize> {
+    let line_start = if line == 0 {
+        0
+    } else {
+        content
+            .match_indices('\n')
+            .nth(line - 1)
+            .map(|(index, _)| index + 1)?
+    };
+
+    let mut col = 0;
+    let within_line = content[line_start..]
+        .char_indices()
+        .find(|(_, c)| {
+            let found = col >= utf16_col || *c == '\n';
+            col += c.len_utf16();
+            found
+        })
+        .map_or(content.len() - line_start, |(index, _)| index);
+
+    Some(line_start + within_line)
+}
+
+/// Converts a UTF-16 column into a character index within the line
+fn char_index(line: &str, utf16_col: usize) -> usize {
+    let mut col = 0;
+
+    line.chars()
+        .take_while(|c| {
+            col += c.len_utf16();
+            col <= utf16_col
+        })
+        .count()
+}
+
+fn display_path(filename: &Path) -> String {
+    std::env::current_dir()
+        .ok()
+        .and_then(|cwd| filename.strip_prefix(cwd).ok())
+        .unwrap_or(filename)
+        .display()
+        .to_string()
+}
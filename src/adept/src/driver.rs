@@ -1,64 +1,365 @@
-use lsp_message::{ExtCompile, LspMessage};
-use request::{Aft, BlockOn, UnwrapAft};
-use std::process::ExitCode;
+use crate::{cli::Format, diagnostics::Renderer};
+use connection::Connection;
+use document::Document;
+use lsp_message::{ExtCompile, ExtResolveDependencies, LspMessage};
+use request::{
+    Aft, BlockOn, BuildOptions, Error, LOCK_FILENAME, PROJECT_FILENAME, Package, PfIn, Project,
+    QueryMode, Rt, TimeoutNever, UnwrapAft, WithErrors,
+};
+use rt_st_in::{ReqCache, RtStIn};
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::Arc,
+};
+use vfs::Canonical;
 
-pub fn compile(filename: &str) -> ExitCode {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("debug")).init();
+const CACHE_FILENAME: &str = "adept.cache";
 
-    let daemon = match daemon_init::connect() {
-        Ok(daemon) => daemon,
-        Err(error) => {
-            log::error!("Failed to connect to daemon - {}", error);
-            return ExitCode::FAILURE;
+/// Builds also list the symbols of the program and record where each package of
+/// a project was found in its `adept.lock`
+pub fn compile(
+    filename: &Path,
+    options: BuildOptions,
+    build: bool,
+    no_daemon: bool,
+    format: Format,
+) -> ExitCode {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let lock = build && filename.is_dir();
+    let result = if no_daemon {
+        compile_in_process(filename, options, lock)
+    } else {
+        compile_with_daemon(filename, options, lock)
+    };
+
+    let exit_code = match result {
+        Ok(compiled) => {
+            let exit_code = report(&compiled.symbols, build, format);
+
+            match compiled
+                .dependencies
+                .map(|dependencies| dependencies.map(write_lockfile))
+            {
+                Some(Ok(Err(error))) => {
+                    report_error(&error, format);
+                    ExitCode::FAILURE
+                }
+                Some(Err(message)) => {
+                    report_message(&message, format);
+                    ExitCode::FAILURE
+                }
+                Some(Ok(Ok(()))) | None => exit_code,
+            }
+        }
+        Err(message) => {
+            report_message(&message, format);
+            ExitCode::FAILURE
         }
     };
 
-    if let Err(err) = LspMessage::send(
-        &daemon,
+    log::info!("Exited");
+    exit_code
+}
+
+struct Compiled {
+    symbols: WithErrors<Arc<[String]>>,
+    /// Only resolved when the project is being built
+    dependencies: Option<Result<Dependencies, String>>,
+}
+
+struct Dependencies {
+    directory: Canonical<PathBuf>,
+    packages: WithErrors<Arc<[Package]>>,
+}
+
+fn compile_with_daemon(
+    filename: &Path,
+    options: BuildOptions,
+    lock: bool,
+) -> Result<Compiled, String> {
+    let daemon = daemon_init::connect()
+        .map_err(|error| format!("Failed to connect to daemon - {}", error))?;
+
+    Ok(Compiled {
+        symbols: query_daemon(&daemon, filename, options)?,
+        dependencies: lock.then(|| query_daemon_dependencies(&daemon, filename)),
+    })
+}
+
+pub fn query_daemon(
+    daemon: &Connection,
+    filename: &Path,
+    options: BuildOptions,
+) -> Result<WithErrors<Arc<[String]>>, String> {
+    LspMessage::send(
+        daemon,
         LspMessage::ExtCompile(ExtCompile {
-            ext_compile: filename.into(),
+            ext_compile: filename.to_string_lossy().into(),
+            ext_options: options,
         }),
-    ) {
-        log::error!("Failed to send compile request - {}", err);
-        return ExitCode::FAILURE;
-    }
+    )
+    .map_err(|error| format!("Failed to send compile request - {}", error))?;
 
-    let message = LspMessage::recv(&daemon);
+    recv_aft(daemon).map(request::ListSymbols::unwrap_aft)
+}
 
-    match message {
-        Ok(Some(LspMessage::ExtAft(aft_result))) => match aft_result.ext_aft {
-            BlockOn::Complete(Some(complete)) => {
-                let aft = Aft::from(complete);
-                let ret = request::ListSymbols::unwrap_aft(aft);
+/// The daemon answers from the cache of the compilation that came before,
+/// so the dependencies aren't resolved a second time
+fn query_daemon_dependencies(
+    daemon: &Connection,
+    directory: &Path,
+) -> Result<Dependencies, String> {
+    let canonical = Canonical::new(directory)
+        .map_err(|_| format!("`{}` does not exist", directory.display()))?;
 
-                for name in ret.value.iter() {
-                    println!(" - {name}");
-                }
-            }
+    LspMessage::send(
+        daemon,
+        LspMessage::ExtResolveDependencies(ExtResolveDependencies {
+            ext_resolve_dependencies: directory.to_string_lossy().into(),
+        }),
+    )
+    .map_err(|error| format!("Failed to send resolve dependencies request - {}", error))?;
+
+    Ok(Dependencies {
+        directory: canonical,
+        packages: recv_aft(daemon).map(request::ResolveDependencies::unwrap_aft)?,
+    })
+}
+
+fn recv_aft(daemon: &Connection) -> Result<Aft<PfIn>, String> {
+    match LspMessage::recv(daemon) {
+        Ok(Some(LspMessage::ExtAft(aft_result))) => match aft_result.ext_aft {
+            BlockOn::Complete(Some(complete)) => Ok(Aft::from(complete)),
             BlockOn::Complete(None) => {
                 unreachable!("result not serializable");
             }
-            BlockOn::Cyclic => {
-                log::info!("Cyclic");
-            }
-            BlockOn::Diverges => {
-                log::info!("Diverges");
-            }
-            BlockOn::TimedOut => {
-                log::info!("Timed out");
-            }
+            other => Err(unfinished(&other)),
         },
-        Ok(Some(LspMessage::ExtError(ext_error))) => {
-            eprintln!("ERROR: {}", ext_error.ext_error);
+        Ok(Some(LspMessage::ExtError(ext_error))) => Err(ext_error.ext_error),
+        Ok(_) => Err("Driver received invalid response".into()),
+        Err(error) => Err(format!("Failed to receive response - {}", error)),
+    }
+}
+
+fn compile_in_process(
+    filename: &Path,
+    options: BuildOptions,
+    lock: bool,
+) -> Result<Compiled, String> {
+    let filename =
+        Canonical::new(filename).map_err(|_| format!("`{}` does not exist", filename.display()))?;
+
+    let connection =
+        Connection::detached().map_err(|_| "Failed to create in-process connection")?;
+
+    // Projects can opt into keeping the request cache between runs
+    let cache_filename = cache_filename(&filename);
+    let cache = cache_filename
+        .as_ref()
+        .map(ReqCache::load)
+        .unwrap_or_default();
+
+    let mut rt = RtStIn::<PfIn>::new(cache);
+    let mut query = rt.query(
+        request::ListSymbols {
+            filename: Arc::new(filename.clone()),
+            options,
         }
-        Ok(_) => {
-            log::error!("Driver received invalid response");
+        .into(),
+        QueryMode::New,
+        connection.dupe(),
+        Box::new(|_, _| ()),
+    );
+
+    let symbols = match rt.block_on(&mut query, TimeoutNever) {
+        Ok(BlockOn::Complete(aft)) => request::ListSymbols::as_aft(aft)
+            .expect("query result matches request")
+            .clone(),
+        Ok(other) => return Err(unfinished(&other)),
+        Err(top_errors) => WithErrors::new(Arc::from([]), top_errors.into()),
+    };
+
+    // Continuing the same revision reuses the dependencies resolved while compiling
+    let dependencies = lock.then(|| {
+        let mut query = rt.query(
+            request::ResolveDependencies {
+                directory: Arc::new(filename.clone()),
+            }
+            .into(),
+            QueryMode::Continue,
+            connection,
+            Box::new(|_, _| ()),
+        );
+
+        match rt.block_on(&mut query, TimeoutNever) {
+            Ok(BlockOn::Complete(aft)) => Ok(Dependencies {
+                directory: filename,
+                packages: request::ResolveDependencies::as_aft(aft)
+                    .expect("query result matches request")
+                    .clone(),
+            }),
+            Ok(other) => Err(unfinished(&other)),
+            Err(_) => Err("Failed to resolve dependencies".into()),
         }
-        Err(error) => {
-            log::error!("Failed to receive response {}", error)
+    });
+
+    if let Some(cache_filename) = cache_filename
+        && rt.cache().save(&cache_filename).is_err()
+    {
+        log::error!("Failed to save cache to {}", cache_filename.display());
+    }
+
+    Ok(Compiled {
+        symbols,
+        dependencies,
+    })
+}
+
+/// Records where each package of a project was found in its `adept.lock`.
+/// Requests never write files, so this is left to the build command.
+fn write_lockfile(dependencies: Dependencies) -> Result<(), Error> {
+    let Dependencies {
+        directory,
+        packages,
+    } = dependencies;
+
+    // Errors in the dependencies were already reported by the build
+    if packages
+        .errors
+        .iter_unordered()
+        .any(|error| error.without_location().is_cyclic_dependency())
+    {
+        return Ok(());
+    }
+
+    let content = request::lockfile(&directory, &packages.value);
+    let filename = directory.join(LOCK_FILENAME);
+
+    // Avoid touching files that are already up to date
+    if std::fs::read_to_string(&filename).is_ok_and(|existing| existing == content) {
+        return Ok(());
+    }
+
+    std::fs::write(&filename, content).map_err(|_| Error::FailedToWriteLockFile)
+}
+
+fn report_error(error: &Error, format: Format) {
+    match format {
+        Format::Human => eprint!("{}", Renderer::new().render(error)),
+        Format::Json => println!("{}", Renderer::new().render_json(error)),
+    }
+}
+
+fn report_message(message: &str, format: Format) {
+    match format {
+        Format::Human => eprint!("{}", Renderer::new().render_message(message)),
+        Format::Json => println!("{}", Renderer::new().render_message_json(message)),
+    }
+}
+
+fn unfinished<T>(block_on: &BlockOn<T>) -> String {
+    match block_on {
+        BlockOn::Complete(_) => "Compilation completed unexpectedly".into(),
+        BlockOn::Cyclic => "Compilation got stuck in a cycle".into(),
+        BlockOn::Diverges => "Compilation diverged".into(),
+        BlockOn::TimedOut => "Compilation timed out".into(),
+    }
+}
+
+fn cache_filename(filename: &Path) -> Option<PathBuf> {
+    let content = std::fs::read_to_string(filename.join(PROJECT_FILENAME)).ok()?;
+    let project = Project::parse(filename.into(), &content).ok()?;
+
+    project
+        .cache_to_disk
+        .unwrap_or(false)
+        .then(|| filename.join(CACHE_FILENAME))
+}
+
+pub fn report(
+    symbols: &WithErrors<Arc<[String]>>,
+    print_symbols: bool,
+    format: Format,
+) -> ExitCode {
+    if print_symbols && format == Format::Human {
+        for name in symbols.value.iter() {
+            println!(" - {name}");
         }
     }
 
-    log::info!("Exited");
-    ExitCode::SUCCESS
+    let mut errors = symbols.errors.iter_unordered().collect::<Vec<_>>();
+    errors.sort_by(|a, b| {
+        a.location()
+            .cmp(&b.location())
+            .then(a.is_warning().cmp(&b.is_warning()))
+            .then(a.cmp(b))
+    });
+
+    errors.dedup();
+
+    // Parser recovery often reports several syntax errors where the same span starts,
+    // only the first is useful to read, but machine-readable output keeps all of them
+    if format == Format::Human {
+        errors.dedup_by(|a, b| {
+            a.without_location().is_syntax()
+                && b.without_location().is_syntax()
+                && start_of(a) == start_of(b)
+        });
+    }
+
+    let mut renderer = Renderer::new();
+    for error in errors.iter() {
+        match format {
+            Format::Human => eprint!("{}", renderer.render(error)),
+            Format::Json => println!("{}", renderer.render_json(error)),
+        }
+    }
+
+    // Warnings are shown, but don't fail compilation
+    let failures = errors.iter().filter(|error| !error.is_warning()).count();
+
+    if failures == 0 {
+        ExitCode::SUCCESS
+    } else {
+        if format == Format::Human {
+            eprintln!("Failed with {failures} error(s)");
+        }
+        ExitCode::FAILURE
+    }
+}
+
+fn start_of(error: &Error) -> Option<(&Path, usize, usize)> {
+    error.location().map(|location| {
+        (
+            location.filename.as_path(),
+            location.start_line,
+            location.start_col,
+        )
+    })
+}
+
+pub fn eval(filename: &Path) -> ExitCode {
+    let content = match std::fs::read_to_string(filename) {
+        Ok(content) => content,
+        Err(error) => {
+            eprintln!("error: failed to read `{}` - {}", filename.display(), error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let document = Document::new(&content);
+    let syntax_tree = parser_adept::reparse(&document, None, document.full_range());
+
+    match kernel::debug_eval(&syntax_tree) {
+        Ok(result) => {
+            println!("{result}");
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
 }
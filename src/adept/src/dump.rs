@@ -0,0 +1,93 @@
+use crate::{cli::DumpKind, cli::Format};
+use document::Document;
+use std::{path::Path, process::ExitCode, sync::Arc};
+use syntax_tree::{BareSyntaxKind, SyntaxNode};
+use text_edit::{TextPointRangeUtf16, TextPointUtf16};
+use util_infinite_iterator::AsIter;
+use util_text::{CharacterPeeker, Positioned};
+
+pub fn dump(kind: DumpKind, filename: &Path, format: Format) -> ExitCode {
+    let content = match std::fs::read_to_string(filename) {
+        Ok(content) => content,
+        Err(error) => {
+            eprintln!("error: failed to read `{}` - {}", filename.display(), error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let document = Document::new(&content);
+
+    match kind {
+        DumpKind::Tokens => dump_tokens(&content, format),
+        DumpKind::Tree => {
+            let syntax_tree = parser_adept::reparse(&document, None, document.full_range());
+
+            match format {
+                Format::Human => dump_tree(&syntax_tree, 0),
+                Format::Json => println!("{}", tree_json(&syntax_tree)),
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn dump_tokens(content: &str, format: Format) {
+    let characters = Positioned::new(content.chars());
+    let mut lexer = lexer_adept::Lexer::new(CharacterPeeker::new(characters));
+
+    for token in lexer.as_iter(true) {
+        let range = TextPointRangeUtf16::new(token.source.point, token.end.point);
+
+        match format {
+            Format::Human => println!("{} {:?}", range, token.kind),
+            Format::Json => println!(
+                "{}",
+                serde_json::json!({
+                    "kind": format!("{:?}", token.kind),
+                    "text": &content[token.source.byte..token.end.byte],
+                    "range": range_json(range),
+                })
+            ),
+        }
+    }
+}
+
+/// Like `SyntaxNode::dump`, but with the range of each node
+fn dump_tree(node: &Arc<SyntaxNode>, depth: usize) {
+    let padding = " ".repeat(depth * 2);
+    let bare = node.bare();
+    let range = node.text_range();
+
+    match (bare.kind(), bare.text()) {
+        (BareSyntaxKind::ColumnSpacing(_) | BareSyntaxKind::LineSpacing(_), Some(_))
+        | (_, None) => {
+            println!("{}{:?} {}", padding, bare.kind(), range)
+        }
+        (_, Some(leaf)) => println!("{}{:?} {}: `{}`", padding, bare.kind(), range, leaf),
+    }
+
+    for child in node.children() {
+        dump_tree(&child, depth + 1);
+    }
+}
+
+fn tree_json(node: &Arc<SyntaxNode>) -> serde_json::Value {
+    let bare = node.bare();
+
+    serde_json::json!({
+        "kind": bare.kind(),
+        "text": bare.text(),
+        "range": range_json(node.text_range()),
+        "children": node.children().map(|child| tree_json(&child)).collect::<Vec<_>>(),
+    })
+}
+
+fn range_json(range: TextPointRangeUtf16) -> serde_json::Value {
+    let point = |point: TextPointUtf16| serde_json::json!({ "line": point.line.0, "character": point.col.0 });
+
+    serde_json::json!({
+        "start": point(range.start()),
+        "end": point(range.end()),
+    })
+}
@@ -1,19 +1,111 @@
+mod cli;
+mod diagnostics;
 mod driver;
+mod dump;
+mod repl;
+mod watch;
 
+use cli::{Cli, Command, DaemonCommand, USAGE};
 use std::process::ExitCode;
 
 fn main() -> ExitCode {
-    let mut args = std::env::args().skip(1).peekable();
+    let cli = match Cli::parse(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(error) => {
+            eprintln!("error: {error}");
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
 
-    match args.peek().map(String::as_str) {
-        Some("-h" | "--help") | None => show_help(),
-        Some("--daemon") => daemon_init::start(),
-        Some("--language-server") => language_server::start(),
-        Some(filename) => driver::compile(filename),
+    if let Some(infrastructure) = &cli.infrastructure
+        && !infrastructure.is_dir()
+    {
+        eprintln!(
+            "error: infrastructure folder `{}` does not exist",
+            infrastructure.display()
+        );
+        return ExitCode::FAILURE;
+    }
+
+    match cli.command {
+        Command::Help => {
+            println!("{USAGE}");
+            ExitCode::FAILURE
+        }
+        Command::Build(path) => {
+            driver::compile(&path, cli.options, true, cli.no_daemon, cli.format)
+        }
+        Command::Check(path) => {
+            driver::compile(&path, cli.options, false, cli.no_daemon, cli.format)
+        }
+        Command::Run(path) => {
+            let exit_code = driver::compile(&path, cli.options, false, cli.no_daemon, cli.format);
+
+            if exit_code == ExitCode::SUCCESS {
+                eprintln!("error: running requires code generation, which is not available yet");
+                return ExitCode::FAILURE;
+            }
+
+            exit_code
+        }
+        Command::Watch(path) => {
+            if cli.no_daemon {
+                eprintln!("error: `watch` uses the daemon and cannot be used with `--no-daemon`");
+                return ExitCode::FAILURE;
+            }
+
+            watch::watch(&path, cli.options, cli.format)
+        }
+        Command::Eval(path) => driver::eval(&path),
+        Command::Dump(kind, path) => dump::dump(kind, &path, cli.format),
+        Command::Daemon(command) => daemon(command),
+        Command::StartDaemon => daemon_init::start(),
+        Command::Lsp => language_server::start(),
+        Command::Repl => repl::repl(),
     }
 }
 
-fn show_help() -> ExitCode {
-    println!("usage: adept FILENAME");
-    ExitCode::FAILURE
+fn daemon(command: DaemonCommand) -> ExitCode {
+    match command {
+        DaemonCommand::Start => {
+            if daemon_init::connect_existing().is_some() {
+                println!("Daemon is already running");
+                return ExitCode::SUCCESS;
+            }
+
+            match daemon_init::connect() {
+                Ok(_) => {
+                    println!("Daemon started");
+                    ExitCode::SUCCESS
+                }
+                Err(error) => {
+                    eprintln!("error: failed to start daemon - {error}");
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        DaemonCommand::Stop => match daemon_init::stop() {
+            Ok(true) => {
+                println!("Daemon stopped");
+                ExitCode::SUCCESS
+            }
+            Ok(false) => {
+                println!("Daemon is not running");
+                ExitCode::SUCCESS
+            }
+            Err(error) => {
+                eprintln!("error: failed to stop daemon - {error}");
+                ExitCode::FAILURE
+            }
+        },
+        DaemonCommand::Status => {
+            if daemon_init::connect_existing().is_some() {
+                println!("Daemon is running");
+            } else {
+                println!("Daemon is not running");
+            }
+            ExitCode::SUCCESS
+        }
+    }
 }
@@ -0,0 +1,199 @@
+use document::Document;
+use parser_adept::Lexed;
+use std::{
+    io::{BufRead, Write},
+    process::ExitCode,
+    sync::Arc,
+};
+use syntax_tree::{BareSyntaxKind, SyntaxNode};
+use text_edit::{TextEditUtf16, TextPointDiffUtf16, TextPointRangeUtf16, TextPointUtf16};
+
+const HELP: &str = "\
+Enter a binding such as `x :: 3` to add it to the session,
+or a term such as `x` to see its normalized value.
+
+commands:
+  :dump        Show the syntax tree of the session
+  :reset       Forget all bindings
+  :help        Show this message
+  :quit        Exit the REPL";
+
+/// Name given to terms entered at the prompt so they can be evaluated as bindings
+const TERM_NAME: &str = "it";
+
+/// The bindings entered so far, kept as a single document that each new
+/// binding is appended to as a text edit
+struct Session {
+    document: Document,
+    syntax_tree: Arc<SyntaxNode>,
+    lexed: Lexed,
+    end: TextPointUtf16,
+}
+
+impl Session {
+    fn new() -> Self {
+        let document = Document::new("");
+        let mut lexed = Lexed::default();
+        let syntax_tree = parser_adept::reparse(&document, Some(&mut lexed), document.full_range());
+
+        Self {
+            document,
+            syntax_tree,
+            lexed,
+            end: TextPointUtf16::start(),
+        }
+    }
+
+    /// Appends `source` to the session, only reparsing what the edit affected
+    fn with(&self, source: &str) -> Self {
+        let separator = if self.end == TextPointUtf16::start() {
+            ""
+        } else {
+            "\n"
+        };
+        let replace_with = format!("{separator}{source}");
+        let end = self.end + TextPointDiffUtf16::of_str(&replace_with);
+
+        let mut document = self.document.clone();
+        document.apply_utf16_text_edit(TextEditUtf16 {
+            range: TextPointRangeUtf16::new(self.end, self.end),
+            replace_with: replace_with.into(),
+        });
+
+        let edited_range =
+            document.translate_utf16_point_range(TextPointRangeUtf16::new(self.end, end));
+        let mut lexed = self.lexed.clone();
+        let syntax_tree = parser_adept::reparse(&document, Some(&mut lexed), edited_range);
+
+        Self {
+            document,
+            syntax_tree,
+            lexed,
+            end,
+        }
+    }
+
+    fn as_term(&self, term: &str) -> Result<Arc<SyntaxNode>, Vec<String>> {
+        let session = self.with(&format!("{TERM_NAME} :: {term}"));
+        check(&session.syntax_tree).map(|_| session.syntax_tree)
+    }
+}
+
+pub fn repl() -> ExitCode {
+    let mut session = Session::new();
+    let mut lines = std::io::stdin().lock().lines();
+
+    println!("Adept REPL - type `:help` for help");
+
+    loop {
+        let Some(input) = read_input(&mut lines) else {
+            return ExitCode::SUCCESS;
+        };
+
+        let input = input.trim();
+        let command = input.split(char::is_whitespace).next().unwrap_or_default();
+
+        match command {
+            "" => (),
+            ":help" => println!("{HELP}"),
+            ":quit" | ":q" => return ExitCode::SUCCESS,
+            ":reset" => {
+                session = Session::new();
+                println!("Cleared all bindings");
+            }
+            ":dump" => {
+                let _ = session.syntax_tree.dump(&mut std::io::stdout(), 0);
+            }
+            _ if command.starts_with(':') => {
+                eprintln!("error: unknown command `{command}`, see `:help`")
+            }
+            _ => enter(&mut session, input),
+        }
+    }
+}
+
+/// Adds a binding to the session, or evaluates a term
+fn enter(session: &mut Session, input: &str) {
+    let extended = session.with(input);
+
+    let errors = match check(&extended.syntax_tree) {
+        Ok(()) => {
+            *session = extended;
+            print_kernel(kernel::debug_eval(&session.syntax_tree));
+            return;
+        }
+        Err(errors) => errors,
+    };
+
+    match session.as_term(input) {
+        Ok(syntax_tree) => print_kernel(kernel::debug_eval(&syntax_tree)),
+        Err(_) => print_errors(&errors),
+    }
+}
+
+/// Reads one entry, which continues onto more lines while brackets are left open
+fn read_input(lines: &mut impl Iterator<Item = std::io::Result<String>>) -> Option<String> {
+    let mut input = String::new();
+    let mut depth = 0;
+
+    loop {
+        print!("{}", if input.is_empty() { "> " } else { "| " });
+        let _ = std::io::stdout().flush();
+
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ if input.is_empty() => return None,
+            _ => return Some(input),
+        };
+
+        for c in line.chars() {
+            match c {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                _ => (),
+            }
+        }
+
+        if !input.is_empty() {
+            input.push('\n');
+        }
+        input.push_str(&line);
+
+        if depth <= 0 {
+            return Some(input);
+        }
+    }
+}
+
+fn check(syntax_tree: &Arc<SyntaxNode>) -> Result<(), Vec<String>> {
+    let mut errors = vec![];
+    let mut stack = vec![syntax_tree.clone()];
+
+    while let Some(node) = stack.pop() {
+        stack.extend(node.children());
+
+        if let BareSyntaxKind::Error { description } = node.bare().kind() {
+            errors.push((node.text_range().start(), description.to_string()));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        errors.sort();
+        Err(errors.into_iter().map(|(_, error)| error).collect())
+    }
+}
+
+fn print_errors(errors: &[String]) {
+    for error in errors {
+        eprintln!("error: {error}");
+    }
+}
+
+fn print_kernel(result: Result<String, kernel::KernelUnavailable>) {
+    match result {
+        Ok(result) => println!("{result}"),
+        Err(error) => eprintln!("error: {error}"),
+    }
+}
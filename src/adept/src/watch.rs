@@ -0,0 +1,158 @@
+use crate::{cli::Format, diagnostics::Renderer, driver};
+use connection::Connection;
+use lsp_message::{ExtListInputs, LspMessage};
+use request::BuildOptions;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Duration,
+};
+
+/// How long to wait for more changes before recompiling, since editors
+/// often save a file using several writes or renames
+const DEBOUNCE: Duration = Duration::from_millis(50);
+
+pub fn watch(filename: &Path, options: BuildOptions, format: Format) -> ExitCode {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let result = daemon_init::connect()
+        .map_err(|error| format!("Failed to connect to daemon - {}", error))
+        .and_then(|daemon| watch_with_daemon(&daemon, filename, &options, format));
+
+    let Err(message) = result;
+    match format {
+        Format::Human => eprint!("{}", Renderer::new().render_message(&message)),
+        Format::Json => println!("{}", Renderer::new().render_message_json(&message)),
+    }
+    ExitCode::FAILURE
+}
+
+fn watch_with_daemon(
+    daemon: &Connection,
+    filename: &Path,
+    options: &BuildOptions,
+    format: Format,
+) -> Result<std::convert::Infallible, String> {
+    loop {
+        // Each query starts a new revision, so only work affected by the change is redone
+        let symbols = driver::query_daemon(daemon, filename, options.clone())?;
+        driver::report(&symbols, false, format);
+
+        let inputs = list_inputs(daemon, filename, options)?;
+
+        if format == Format::Human {
+            eprintln!("Watching {} path(s) for changes...", inputs.len());
+        }
+
+        wait_for_change(&inputs)?;
+    }
+}
+
+/// Asks the daemon which paths the last check of `filename` depended on,
+/// including imports that were missing
+fn list_inputs(
+    daemon: &Connection,
+    filename: &Path,
+    options: &BuildOptions,
+) -> Result<HashSet<PathBuf>, String> {
+    LspMessage::send(
+        daemon,
+        LspMessage::ExtListInputs(ExtListInputs {
+            ext_list_inputs: filename.to_string_lossy().into(),
+            ext_options: options.clone(),
+        }),
+    )
+    .map_err(|error| format!("Failed to send list inputs request - {}", error))?;
+
+    match LspMessage::recv(daemon) {
+        Ok(Some(LspMessage::ExtInputs(inputs))) => Ok(inputs
+            .ext_inputs
+            .iter()
+            .map(|input| normalize(input.as_ref()))
+            .collect()),
+        Ok(Some(LspMessage::ExtError(ext_error))) => Err(ext_error.ext_error),
+        Ok(_) => Err("Driver received invalid response".into()),
+        Err(error) => Err(format!("Failed to receive response - {}", error)),
+    }
+}
+
+/// Missing paths can't be canonicalized, but their directory often can,
+/// which lets events be matched against them
+fn normalize(path: &Path) -> PathBuf {
+    path.parent()
+        .zip(path.file_name())
+        .and_then(|(directory, name)| Some(directory.canonicalize().ok()?.join(name)))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+#[cfg(target_os = "linux")]
+fn wait_for_change(inputs: &HashSet<PathBuf>) -> Result<(), String> {
+    use inotify::{Inotify, WatchMask};
+
+    let mut inotify =
+        Inotify::init().map_err(|error| format!("Failed to initialize inotify - {}", error))?;
+
+    // Directories are watched instead of the files themselves, so that files
+    // replaced by renaming over them, or created later, are still noticed
+    let mut directories = HashMap::new();
+    for directory in inputs.iter().flat_map(|input| input.parent()) {
+        if directories.values().any(|watched| watched == directory) {
+            continue;
+        }
+
+        let mask = WatchMask::MODIFY
+            | WatchMask::CLOSE_WRITE
+            | WatchMask::CREATE
+            | WatchMask::DELETE
+            | WatchMask::MOVED_FROM
+            | WatchMask::MOVED_TO;
+
+        match inotify.watches().add(directory, mask) {
+            Ok(descriptor) => {
+                directories.insert(descriptor, directory.to_path_buf());
+            }
+            Err(error) => log::warn!("Failed to watch {} - {}", directory.display(), error),
+        }
+    }
+
+    if directories.is_empty() {
+        return Err("No files to watch".into());
+    }
+
+    let mut buffer = [0; 4096];
+
+    loop {
+        let events = inotify
+            .read_events_blocking(&mut buffer)
+            .map_err(|error| format!("Failed to read file events - {}", error))?;
+
+        let changed = events.into_iter().any(|event| {
+            let Some((directory, name)) = directories.get(&event.wd).zip(event.name) else {
+                return false;
+            };
+            inputs.contains(&directory.join(name))
+        });
+
+        if changed {
+            break;
+        }
+    }
+
+    // Drain any related events that arrive shortly after the first one
+    std::thread::sleep(DEBOUNCE);
+    while inotify
+        .read_events(&mut buffer)
+        .is_ok_and(|mut events| events.next().is_some())
+    {}
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn wait_for_change(_inputs: &HashSet<PathBuf>) -> Result<(), String> {
+    Err(
+        "Watching files is only supported on Linux, run `adept check` after making changes instead"
+            .into(),
+    )
+}
@@ -30,6 +30,23 @@ impl Connection {
         }
     }
 
+    /// Creates a connection that isn't attached to any other process,
+    /// for running queries in-process
+    pub fn detached() -> Result<Self, ()> {
+        #[cfg(target_family = "unix")]
+        {
+            UnixStream::pair()
+                .map(|(stream, _)| Self::new_unix(stream))
+                .map_err(|_| ())
+        }
+
+        #[cfg(target_family = "windows")]
+        {
+            // There's no in-process stream pair to use yet
+            Err(())
+        }
+    }
+
     pub fn dupe(&self) -> Self {
         #[cfg(target_family = "unix")]
         {
@@ -5,7 +5,14 @@ use request::PfIn;
 use rt_st_in::{RtStIn, RtStInQuery};
 #[cfg(target_family = "unix")]
 use std::os::unix::net::UnixListener;
-use std::{collections::VecDeque, io, sync::Mutex};
+use std::{
+    collections::VecDeque,
+    io,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
 
 pub struct Daemon {
     #[cfg(target_family = "unix")]
@@ -13,6 +20,7 @@ pub struct Daemon {
     pub idle_tracker: IdleTracker,
     pub rt: Mutex<RtStIn<'static, PfIn>>,
     pub queries: Mutex<VecDeque<RtStInQuery<'static, PfIn>>>,
+    pub stop_requested: AtomicBool,
 }
 
 impl Daemon {
@@ -26,6 +34,7 @@ impl Daemon {
             idle_tracker: IdleTracker::new(Duration::from_secs(5)),
             rt: Mutex::new(RtStIn::new(ReqCache::default())),
             queries: Mutex::new(VecDeque::default()),
+            stop_requested: AtomicBool::new(false),
         }
     }
 
@@ -38,6 +47,10 @@ impl Daemon {
     }
 
     pub fn should_exit(&self) -> bool {
-        self.idle_tracker.should_shutdown()
+        self.stop_requested.load(Ordering::Relaxed) || self.idle_tracker.should_shutdown()
+    }
+
+    pub fn request_stop(&self) {
+        self.stop_requested.store(true, Ordering::Relaxed);
     }
 }
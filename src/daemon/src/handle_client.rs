@@ -3,20 +3,27 @@ use connection::Connection;
 use document::Document;
 use file_cache::{Canonical, FileBytes, FileCache, FileContent, FileId, FileKind};
 use file_uri::DecodeFileUri;
-use lsp_message::{ExtError, LspMessage, LspNotification, LspRequest, LspRequestId, LspResponse};
+use lsp_message::{
+    ExtError, ExtInputs, LspMessage, LspNotification, LspRequest, LspRequestId, LspResponse,
+};
 use lsp_types::{
     CompletionItem, CompletionItemKind, CompletionList, CompletionParams, CompletionResponse,
     Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidOpenTextDocumentParams,
     DocumentDiagnosticParams, DocumentDiagnosticReport, DocumentDiagnosticReportResult,
-    ExecuteCommandParams, FullDocumentDiagnosticReport, RelatedFullDocumentDiagnosticReport, Uri,
+    Documentation, ExecuteCommandParams, FullDocumentDiagnosticReport, MarkupContent, MarkupKind,
+    RelatedFullDocumentDiagnosticReport, Uri,
 };
-use request::{BlockOn, Cache, QueryMode, Rt};
+use parser_adept::Lexed;
+use request::{BlockOn, Cache, QueryMode, Rt, TimeoutNever, UnwrapAft};
 use std::{
     borrow::Cow, ffi::OsStr, io::ErrorKind, panic::catch_unwind, path::PathBuf, str::FromStr,
     sync::Arc,
 };
-use syntax_tree::{BareSyntaxKind, BuiltinType};
-use text_edit::TextEditOrFullUtf16;
+use syntax_tree::{
+    BareSyntaxKind, SyntaxNode,
+    ast::{self, AstNode},
+};
+use text_edit::{TextEditOrFullUtf16, TextPointDiffUtf16, TextPointRangeUtf16};
 
 pub struct Client {
     file_cache: FileCache,
@@ -39,6 +46,44 @@ impl Client {
                     .map(|file_content| (file_content, file_id, filepath))
             })
     }
+
+    /// Imported files that are open in the editor use their editor content, and the rest are
+    /// parsed through the runtime, which reads them again once they may have changed
+    pub fn get_imported_syntax_tree(
+        &mut self,
+        daemon: &Daemon,
+        connection: &Connection,
+        relative_to: FileId,
+        relative_path: &str,
+    ) -> Option<Arc<SyntaxNode>> {
+        let file_id = self.file_cache.register_file(relative_to, relative_path)?;
+
+        if let Some(file_content) = self.file_cache.get_content(file_id) {
+            return file_content.syntax_tree.clone();
+        }
+
+        let filename = self.file_cache.get_path(file_id)?;
+        let mut rt = daemon.rt.lock().unwrap();
+        let mut query = rt.query(
+            request::ParseFile {
+                filename: Arc::new(filename),
+            }
+            .into(),
+            QueryMode::Continue,
+            connection.dupe(),
+            Box::new(|_, _| ()),
+        );
+
+        let Ok(BlockOn::Complete(aft)) = rt.block_on(&mut query, TimeoutNever) else {
+            return None;
+        };
+
+        request::ParseFile::as_aft(aft)
+            .expect("query result matches request")
+            .value
+            .as_ref()
+            .map(|syntax_tree| syntax_tree.0.clone())
+    }
 }
 
 pub enum ConfigFile {
@@ -109,7 +154,7 @@ pub fn handle_client(daemon: &Daemon, connection: Connection, desc: String) {
                 })
                 .or_else(|request| {
                     on_request::<lsp_types::request::Completion>(request, |id, params| {
-                        completion(&mut client, id, params)
+                        completion(&mut client, daemon, &connection, id, params)
                     })
                 })
                 .or_else(|request| {
@@ -161,6 +206,7 @@ pub fn handle_client(daemon: &Daemon, connection: Connection, desc: String) {
                     rt.query(
                         request::ListSymbols {
                             filename: Arc::new(filename),
+                            options: compile.ext_options,
                         }
                         .into(),
                         QueryMode::New,
@@ -189,6 +235,74 @@ pub fn handle_client(daemon: &Daemon, connection: Connection, desc: String) {
             Ok(Some(LspMessage::ExtError(_))) => {
                 log::error!("Client sent ext error message");
             }
+            Ok(Some(LspMessage::ExtListInputs(list_inputs))) => {
+                let Ok(filename) = Canonical::new(&list_inputs.ext_list_inputs) else {
+                    let response = LspMessage::ExtError(ExtError {
+                        ext_error: format!("`{}` does not exist", list_inputs.ext_list_inputs),
+                    });
+                    let _ = LspMessage::send(&connection, response);
+                    continue;
+                };
+
+                let req = request::ListSymbols {
+                    filename: Arc::new(filename),
+                    options: list_inputs.ext_options,
+                }
+                .into();
+
+                let inputs = daemon
+                    .rt
+                    .lock()
+                    .unwrap()
+                    .inputs(&req)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect();
+
+                let response = LspMessage::ExtInputs(ExtInputs { ext_inputs: inputs });
+                let _ = LspMessage::send(&connection, response);
+            }
+            Ok(Some(LspMessage::ExtInputs(_))) => {
+                log::error!("Client sent ext inputs message");
+            }
+            Ok(Some(LspMessage::ExtResolveDependencies(resolve))) => {
+                let Ok(directory) = Canonical::new(&resolve.ext_resolve_dependencies) else {
+                    let response = LspMessage::ExtError(ExtError {
+                        ext_error: format!("`{}` does not exist", resolve.ext_resolve_dependencies),
+                    });
+                    let _ = LspMessage::send(&connection, response);
+                    continue;
+                };
+
+                // Continuing the current revision reuses the dependencies resolved by
+                // the compilation that came before
+                let mut rt = daemon.rt.lock().unwrap();
+                let mut query = rt.query(
+                    request::ResolveDependencies {
+                        directory: Arc::new(directory),
+                    }
+                    .into(),
+                    QueryMode::Continue,
+                    connection.dupe(),
+                    Box::new(|_, _| ()),
+                );
+
+                let response = match rt.block_on(&mut query, TimeoutNever) {
+                    Ok(BlockOn::Complete(aft)) => {
+                        LspMessage::ExtAft(BlockOn::Complete(aft.cache().cloned()).into())
+                    }
+                    Ok(_) | Err(_) => LspMessage::ExtError(ExtError {
+                        ext_error: "Failed to resolve dependencies".into(),
+                    }),
+                };
+                let _ = LspMessage::send(&connection, response);
+            }
+            Ok(Some(LspMessage::ExtStop(_))) => {
+                log::info!("Client requested daemon to stop");
+                daemon.request_stop();
+                break;
+            }
             Err(error) => {
                 if let ErrorKind::WouldBlock = error.kind() {
                     // No message is ready to receive from the client yet
@@ -266,6 +380,16 @@ fn document_diagnostics_request(
                 });
             }
         }
+
+        for warning in syntax_tree.identifier_warnings() {
+            diagnostics.push(Diagnostic {
+                range: warning.range.into(),
+                severity: Some(DiagnosticSeverity::WARNING),
+                source: Some("Adept".into()),
+                message: warning.to_string(),
+                ..Default::default()
+            });
+        }
     }
 
     Ok(DocumentDiagnosticReportResult::Report(
@@ -281,77 +405,83 @@ fn document_diagnostics_request(
 
 fn completion(
     client: &mut Client,
+    daemon: &Daemon,
+    connection: &Connection,
     _id: &LspRequestId,
     params: CompletionParams,
 ) -> Result<Option<CompletionResponse>, LspResponse> {
     let text_document = &params.text_document_position.text_document;
     let mut items = vec![];
 
-    let Some((file_content, _, _)) = client.get_file_content(&text_document.uri) else {
+    let Some((file_content, file_id, _)) = client.get_file_content(&text_document.uri) else {
         return Ok(Some(CompletionResponse::List(CompletionList {
             is_incomplete: true,
             items,
         })));
     };
 
-    struct BindingInfo<'a> {
-        name: &'a str,
+    let imported_syntax_trees = Vec::from_iter(
+        file_content
+            .syntax_tree
+            .iter()
+            .flat_map(|syntax_tree| ast::Root::cast(syntax_tree.clone()))
+            .flat_map(|root| root.imports())
+            .flat_map(|import| import.path())
+            .flat_map(|path| client.get_imported_syntax_tree(daemon, connection, file_id, &path)),
+    );
+
+    struct BindingInfo {
+        name: Arc<str>,
         kind: Option<CompletionItemKind>,
+        doc: Option<String>,
     }
 
-    if let Some(syntax_tree) = &file_content.syntax_tree {
-        let binding_names = syntax_tree
-            .bare()
-            .children()
-            .filter(|x| matches!(x.kind(), BareSyntaxKind::Binding))
-            .flat_map(|binding| {
-                let name = binding
-                    .children()
-                    .find(|child| matches!(child.kind(), BareSyntaxKind::Name))
-                    .and_then(|name| {
-                        name.children().find_map(|id| match id.kind() {
-                            BareSyntaxKind::Identifier(name) => Some(name),
-                            _ => None,
-                        })
-                    });
-
-                let kind = binding
-                    .children()
-                    .find_map(|child| match child.kind() {
-                        BareSyntaxKind::Term => Some(child),
-                        _ => None,
-                    })
-                    .and_then(|term| {
-                        term.children().find_map(|child| match child.kind() {
-                            BareSyntaxKind::BuiltinType(BuiltinType::Fn) => {
-                                Some(CompletionItemKind::INTERFACE)
-                            }
-                            BareSyntaxKind::BuiltinType(BuiltinType::Record) => {
-                                Some(CompletionItemKind::STRUCT)
-                            }
-                            BareSyntaxKind::BuiltinType(
-                                BuiltinType::Bool
-                                | BuiltinType::Void
-                                | BuiltinType::Nat
-                                | BuiltinType::Type,
-                            ) => Some(CompletionItemKind::ENUM),
-                            BareSyntaxKind::FnValue => Some(CompletionItemKind::FUNCTION),
-                            BareSyntaxKind::TrueValue
-                            | BareSyntaxKind::FalseValue
-                            | BareSyntaxKind::VoidValue
-                            | BareSyntaxKind::IfValue
-                            | BareSyntaxKind::Block
-                            | BareSyntaxKind::Variable(_) => Some(CompletionItemKind::VALUE),
-                            _ => None,
-                        })
-                    });
+    for syntax_tree in file_content
+        .syntax_tree
+        .iter()
+        .chain(imported_syntax_trees.iter())
+    {
+        let Some(root) = ast::Root::cast(syntax_tree.clone()) else {
+            continue;
+        };
+
+        let binding_names = root.bindings().flat_map(|binding| {
+            let kind = binding
+                .value()
+                .and_then(|term| term.expr())
+                .and_then(|expr| match expr {
+                    ast::Expr::FnType(_) => Some(CompletionItemKind::INTERFACE),
+                    ast::Expr::RecordType(_) => Some(CompletionItemKind::STRUCT),
+                    ast::Expr::PrimitiveType(_) | ast::Expr::VariantType(_) => {
+                        Some(CompletionItemKind::ENUM)
+                    }
+                    ast::Expr::Fn(_) => Some(CompletionItemKind::FUNCTION),
+                    ast::Expr::Literal(_)
+                    | ast::Expr::If(_)
+                    | ast::Expr::Block(_)
+                    | ast::Expr::Variable(_) => Some(CompletionItemKind::VALUE),
+                    _ => None,
+                });
 
-                name.map(|name| BindingInfo { name, kind })
-            });
+            binding
+                .name()
+                .and_then(|name| name.text())
+                .map(|name| BindingInfo {
+                    name,
+                    kind,
+                    doc: binding.doc(),
+                })
+        });
 
         items.extend(binding_names.map(|info| CompletionItem {
             label: info.name.to_string(),
             kind: info.kind,
+            documentation: info.doc.map(|doc| {
+                Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: doc,
+                })
+            }),
             ..Default::default()
         }));
     }
@@ -448,6 +578,7 @@ fn execute_command(
                         .as_ref()
                         .map(|syntax_tree| {
                             catch_unwind(|| kernel::debug_eval(syntax_tree))
+                                .map(|result| result.unwrap_or_else(|error| error.to_string()))
                                 .unwrap_or_else(|e| format!("<paniced>: {e:?}"))
                         })
                 })
@@ -482,7 +613,9 @@ fn did_open(client: &mut Client, params: DidOpenTextDocumentParams) {
             };
 
             let document = Document::new(&params.text_document.text);
-            let syntax_tree = parser_adept::reparse(&document, None, document.full_range());
+            let mut lexed = Lexed::default();
+            let syntax_tree =
+                parser_adept::reparse(&document, Some(&mut lexed), document.full_range());
 
             let file_bytes = FileBytes::Document(document);
             let file_id = client.file_cache.preregister_file(Cow::Owned(filepath));
@@ -494,6 +627,7 @@ fn did_open(client: &mut Client, params: DidOpenTextDocumentParams) {
                     kind,
                     file_bytes,
                     syntax_tree: Some(syntax_tree),
+                    lexed,
                 },
             );
         }
@@ -516,12 +650,25 @@ fn did_change(client: &mut Client, params: DidChangeTextDocumentParams) {
         let mut file_content = file_content.after_edits(std::iter::empty());
 
         for edit in edits {
+            // Where the replacement text ends up in the edited document
+            let edited = edit.range.map(|range| {
+                let start = range.start();
+                TextPointRangeUtf16::new(
+                    start,
+                    start + TextPointDiffUtf16::of_str(&edit.replace_with),
+                )
+            });
+
             file_content = file_content.after_edits(std::iter::once(edit));
 
             if let Some(document) = file_content.file_bytes.as_document() {
-                let old_syntax_tree = file_content.syntax_tree.clone();
+                let range = edited.map_or_else(
+                    || document.full_range(),
+                    |edited| document.translate_utf16_point_range(edited),
+                );
+
                 let new_syntax_tree =
-                    parser_adept::reparse(document, old_syntax_tree, document.full_range());
+                    parser_adept::reparse(document, Some(&mut file_content.lexed), range);
                 file_content.syntax_tree = Some(new_syntax_tree);
             }
         }
@@ -3,6 +3,7 @@ mod error;
 use connection::Connection;
 use daemon::Daemon;
 pub use error::*;
+use lsp_message::{ExtStop, LspMessage};
 use std::{
     fs::remove_file,
     io,
@@ -91,9 +92,8 @@ pub fn connect() -> Result<Connection, StartError> {
     let filepath = cwd.join("adeptd.lock");
 
     // 1) Check if we can connect to Unix Domain Socket
-    if let Ok(connection) = Connection::connect(&filepath) {
+    if let Some(connection) = connect_existing() {
         // 2) If okay, then this client has established a connection.
-        log::info!("Connected to existing daemon instance");
         return Ok(connection);
     }
 
@@ -114,6 +114,26 @@ pub fn connect() -> Result<Connection, StartError> {
     Err(StartError::FailedToStart)
 }
 
+/// Tries to connect to an already running daemon process without
+/// launching a new one.
+pub fn connect_existing() -> Option<Connection> {
+    let cwd = std::env::current_dir().expect("Failed to get current directory");
+    let connection = Connection::connect(&cwd.join("adeptd.lock")).ok()?;
+    log::info!("Connected to existing daemon instance");
+    Some(connection)
+}
+
+/// Asks the running daemon process to shut down. Returns whether
+/// there was a daemon to stop.
+pub fn stop() -> io::Result<bool> {
+    let Some(connection) = connect_existing() else {
+        return Ok(false);
+    };
+
+    LspMessage::send(&connection, LspMessage::ExtStop(ExtStop { ext_stop: () }))?;
+    Ok(true)
+}
+
 pub fn spawn() -> std::io::Result<()> {
     let exe = std::env::current_exe()?;
 
@@ -9,6 +9,11 @@ impl Canonical<PathBuf> {
     pub fn new(path: impl AsRef<Path>) -> Result<Self, ()> {
         Ok(Self(std::fs::canonicalize(path).map_err(|_| ())?))
     }
+
+    pub fn resolve_relative(&self, relative_path: impl AsRef<Path>) -> Result<Self, ()> {
+        let directory = self.0.parent().unwrap_or(&self.0);
+        Self::new(directory.join(relative_path))
+    }
 }
 
 impl<P: AsRef<Path>> AsRef<Path> for Canonical<P> {
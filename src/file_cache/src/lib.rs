@@ -6,6 +6,7 @@ pub use crate::path_interner::FileId;
 use crate::path_interner::PathInterner;
 pub use canonical::Canonical;
 pub use file_bytes::FileBytes;
+use parser_adept::Lexed;
 use std::{
     borrow::Cow,
     collections::HashMap,
@@ -33,6 +34,8 @@ pub struct FileContent {
     pub kind: FileKind,
     pub file_bytes: FileBytes,
     pub syntax_tree: Option<Arc<SyntaxNode>>,
+    /// Tokens of the content the syntax tree was parsed from, for reparsing after edits
+    pub lexed: Lexed,
 }
 
 impl FileContent {
@@ -41,19 +44,30 @@ impl FileContent {
             kind: self.kind,
             file_bytes: self.file_bytes.after_edits(edits),
             syntax_tree: None,
+            lexed: self.lexed.clone(),
         }
     }
 }
 
 impl FileCache {
-    pub fn register_file(&self, _relative_to: FileId, _relative_path: &str) -> Option<FileId> {
-        todo!("FileCache::register_file")
+    pub fn register_file(&self, relative_to: FileId, relative_path: &str) -> Option<FileId> {
+        let filepath = self
+            .path_interner
+            .path(relative_to)?
+            .resolve_relative(relative_path)
+            .ok()?;
+
+        Some(self.path_interner.intern(Cow::Owned(filepath)))
     }
 
     pub fn preregister_file(&mut self, filepath: Cow<Canonical<PathBuf>>) -> FileId {
         self.path_interner.intern_mut(filepath)
     }
 
+    pub fn get_path(&self, file_id: FileId) -> Option<Canonical<PathBuf>> {
+        self.path_interner.path(file_id)
+    }
+
     pub fn get_content(&mut self, file_id: FileId) -> Option<Arc<FileContent>> {
         self.files.lock().unwrap().get(&file_id).cloned()
     }
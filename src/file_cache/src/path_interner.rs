@@ -17,7 +17,6 @@ pub struct PathInternerInner {
 }
 
 impl PathInterner {
-    #[allow(unused)]
     pub fn intern(&self, filepath: Cow<Canonical<PathBuf>>) -> FileId {
         self.inner.lock().unwrap().intern(filepath)
     }
@@ -25,6 +24,10 @@ impl PathInterner {
     pub fn intern_mut(&mut self, filepath: Cow<Canonical<PathBuf>>) -> FileId {
         self.inner.get_mut().unwrap().intern(filepath)
     }
+
+    pub fn path(&self, file_id: FileId) -> Option<Canonical<PathBuf>> {
+        self.inner.lock().unwrap().paths.get(&file_id).cloned()
+    }
 }
 
 impl PathInternerInner {
@@ -1,18 +1,19 @@
 #[cfg(feature = "kernel")]
 mod v1;
 
-#[cfg(feature = "kernel")]
-pub use v1::*;
+use std::sync::Arc;
+use syntax_tree::SyntaxNode;
 
-#[cfg(not(feature = "kernel"))]
-mod non_implementation {
-    use std::sync::Arc;
-    use syntax_tree::SyntaxNode;
+#[derive(Copy, Clone, Debug, thiserror::Error)]
+#[error("the kernel is not available in this build")]
+pub struct KernelUnavailable;
 
-    pub fn debug_eval(_: &Arc<SyntaxNode>) -> String {
-        todo!("for testing only")
-    }
+#[cfg(feature = "kernel")]
+pub fn debug_eval(syntax_tree: &Arc<SyntaxNode>) -> Result<String, KernelUnavailable> {
+    Ok(v1::debug_eval(syntax_tree))
 }
 
 #[cfg(not(feature = "kernel"))]
-pub use non_implementation::*;
+pub fn debug_eval(_: &Arc<SyntaxNode>) -> Result<String, KernelUnavailable> {
+    Err(KernelUnavailable)
+}
@@ -132,6 +132,20 @@ pub fn start() -> ExitCode {
                 LspMessage::ExtError(_) => {
                     log::error!("Language server does not support ext error message");
                 }
+                LspMessage::ExtStop(_) => {
+                    log::error!("Language server does not support ext stop message");
+                }
+                LspMessage::ExtListInputs(_) => {
+                    log::error!("Language server does not support ext list inputs message");
+                }
+                LspMessage::ExtInputs(_) => {
+                    log::error!("Language server does not support ext inputs message");
+                }
+                LspMessage::ExtResolveDependencies(_) => {
+                    log::error!(
+                        "Language server does not support ext resolve dependencies message"
+                    );
+                }
             }
         }
     }
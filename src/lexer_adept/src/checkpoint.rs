@@ -0,0 +1,266 @@
+use crate::{Lexer, LexerState};
+use std::str::Chars;
+use text_edit::{LineIndex, SourcePosition, TextPointUtf16};
+use token::Token;
+use util_infinite_iterator::InfiniteIterator;
+use util_text::{CharacterPeeker, Positioned};
+
+/// A token boundary from which lexing can be resumed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub position: SourcePosition,
+    pub state: LexerState,
+}
+
+impl Checkpoint {
+    pub const START: Self = Self {
+        position: SourcePosition::start(),
+        state: LexerState {
+            unary_call: false,
+            after_dot: false,
+        },
+    };
+
+    fn shifted(&self, bytes: isize, lines: isize) -> Self {
+        Self {
+            position: shifted(self.position, bytes, lines),
+            state: self.state,
+        }
+    }
+}
+
+fn shifted(position: SourcePosition, bytes: isize, lines: isize) -> SourcePosition {
+    SourcePosition {
+        byte: position.byte.strict_add_signed(bytes),
+        point: TextPointUtf16 {
+            line: LineIndex(position.point.line.0.strict_add_signed(lines)),
+            col: position.point.col,
+        },
+    }
+}
+
+/// The checkpoint to resume lexing from for each line, which is the last token
+/// boundary at or before the start of the line. Lines that start inside of a
+/// token, such as a multiline comment, resume from the start of that token.
+#[derive(Clone, Debug, Default)]
+pub struct LineCheckpoints {
+    lines: Vec<Checkpoint>,
+    len: usize,
+}
+
+/// The lines affected by an edit, where `old_end` and `new_end` are the last
+/// affected line before and after the edit
+#[derive(Copy, Clone, Debug)]
+pub struct LineEdit {
+    pub start: usize,
+    pub old_end: usize,
+    pub new_end: usize,
+}
+
+pub struct Relexed {
+    pub checkpoints: LineCheckpoints,
+    /// Every token of the new content, ending with the end of file token. Only the
+    /// tokens from the resume point until the lexer re-synchronized with the old
+    /// token stream are lexed again, the rest are kept from the old tokens.
+    pub tokens: Vec<Token<SourcePosition>>,
+}
+
+impl LineCheckpoints {
+    pub fn record(content: &str) -> Self {
+        Self::default()
+            .relex(&[], content, LineEdit::everything())
+            .checkpoints
+    }
+
+    pub fn get(&self, line: usize) -> Checkpoint {
+        self.lines
+            .get(line)
+            .or(self.lines.last())
+            .copied()
+            .unwrap_or(Checkpoint::START)
+    }
+
+    /// Lexes the new `content` starting from the nearest checkpoint before the edit,
+    /// stopping once a line after the edit resumes from the same state as before.
+    /// The `tokens` must be the ones lexed from the old content.
+    pub fn relex(
+        &self,
+        tokens: &[Token<SourcePosition>],
+        content: &str,
+        edit: LineEdit,
+    ) -> Relexed {
+        let resume = self.get(edit.start);
+        let bytes = content.len() as isize - self.len as isize;
+        let lines = edit.new_end as isize - edit.old_end as isize;
+
+        let characters =
+            Positioned::resume(content[resume.position.byte..].chars(), resume.position);
+        let lexer = Lexer::resume(CharacterPeeker::new(characters), resume.state);
+
+        let prefix = self.lines[..edit.start.min(self.lines.len())].to_vec();
+        let kept = tokens
+            .iter()
+            .take_while(|token| {
+                !token.kind.is_end_of_file() && token.end.byte <= resume.position.byte
+            })
+            .cloned()
+            .collect();
+        let mut recorder = Recorder::new(prefix, resume, kept);
+
+        let resynchronized = recorder.run(lexer, |recorder| {
+            let line = recorder.lines.len() - 1;
+            let old = self.lines.get(line.checked_add_signed(-lines)?)?;
+
+            (line > edit.new_end
+                && old.position.point.line.0 > edit.old_end
+                && old.shifted(bytes, lines) == recorder.lines[line])
+                .then_some(line)
+        });
+
+        let Some(line) = resynchronized else {
+            return Relexed {
+                checkpoints: Self {
+                    lines: recorder.lines,
+                    len: content.len(),
+                },
+                tokens: recorder.tokens,
+            };
+        };
+
+        let end = recorder.lines[line].position.byte;
+        let old_line = line.strict_add_signed(-lines);
+        let old_end = self.lines[old_line].position.byte;
+
+        let mut new_tokens = recorder.tokens;
+        new_tokens.retain(|token| token.end.byte <= end);
+        new_tokens.extend(
+            tokens
+                .iter()
+                .skip_while(|token| token.source.byte < old_end)
+                .map(|token| Token {
+                    kind: token.kind.clone(),
+                    source: shifted(token.source, bytes, lines),
+                    end: shifted(token.end, bytes, lines),
+                }),
+        );
+
+        let mut checkpoints = recorder.lines;
+        checkpoints.truncate(line + 1);
+        checkpoints.extend(
+            self.lines[old_line + 1..]
+                .iter()
+                .map(|checkpoint| checkpoint.shifted(bytes, lines)),
+        );
+
+        Relexed {
+            checkpoints: Self {
+                lines: checkpoints,
+                len: content.len(),
+            },
+            tokens: new_tokens,
+        }
+    }
+
+    /// Lexes `content` from the checkpoint of `line` onward, which gives the same tokens as
+    /// lexing the whole file as long as nothing before that line was changed
+    pub fn resume<'a>(
+        &self,
+        content: &'a str,
+        line: usize,
+    ) -> Lexer<CharacterPeeker<Positioned<Chars<'a>>, SourcePosition>, SourcePosition> {
+        let checkpoint = self.get(line);
+        let characters = Positioned::resume(
+            content[checkpoint.position.byte..].chars(),
+            checkpoint.position,
+        );
+
+        Lexer::resume(CharacterPeeker::new(characters), checkpoint.state)
+    }
+}
+
+impl LineEdit {
+    /// An edit that replaced all of the content, so nothing can be kept
+    pub fn everything() -> Self {
+        Self {
+            start: 0,
+            old_end: usize::MAX,
+            new_end: usize::MAX,
+        }
+    }
+}
+
+struct Recorder {
+    lines: Vec<Checkpoint>,
+    previous: Checkpoint,
+    tokens: Vec<Token<SourcePosition>>,
+}
+
+impl Recorder {
+    fn new(lines: Vec<Checkpoint>, start: Checkpoint, tokens: Vec<Token<SourcePosition>>) -> Self {
+        let mut recorder = Self {
+            lines,
+            previous: start,
+            tokens,
+        };
+        recorder.push(start, &mut |_| None::<()>);
+        recorder
+    }
+
+    /// Lexes until the end of the file, or until `stop` gives a result
+    /// after a line is recorded
+    fn run<L, T>(
+        &mut self,
+        mut lexer: Lexer<L, SourcePosition>,
+        mut stop: impl FnMut(&Self) -> Option<T>,
+    ) -> Option<T>
+    where
+        L: util_text::Lexable<SourcePosition> + Send,
+    {
+        loop {
+            let token = lexer.next();
+            let end = token.end;
+            let is_end_of_file = token.kind.is_end_of_file();
+            self.tokens.push(token);
+
+            if is_end_of_file {
+                return None;
+            }
+
+            if let Some(state) = lexer.snapshot() {
+                let checkpoint = Checkpoint {
+                    position: end,
+                    state,
+                };
+
+                if let Some(result) = self.push(checkpoint, &mut stop) {
+                    return Some(result);
+                }
+            }
+        }
+    }
+
+    fn push<T>(
+        &mut self,
+        checkpoint: Checkpoint,
+        stop: &mut impl FnMut(&Self) -> Option<T>,
+    ) -> Option<T> {
+        let point = checkpoint.position.point;
+
+        while self.lines.len() <= point.line.0 {
+            let line = self.lines.len();
+            self.lines
+                .push(if line == point.line.0 && point.col.0 == 0 {
+                    checkpoint
+                } else {
+                    self.previous
+                });
+
+            if let Some(result) = stop(self) {
+                return Some(result);
+            }
+        }
+
+        self.previous = checkpoint;
+        None
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{Lexer, feed_result::FeedResult};
-use token::Token;
+use token::{Punct, Token};
 use util_infinite_iterator::InfiniteIterator;
 use util_text::Lexable;
 
@@ -12,7 +12,10 @@ where
 
     fn next(&mut self) -> Self::Item {
         loop {
-            if let FeedResult::Has(token) = self.feed() {
+            if let FeedResult::Has(mut token) = self.feed() {
+                // A token is produced right after its last character is consumed
+                token.end = self.lexable.peek().source();
+                self.after_dot = token.kind.is_punct_of(Punct::new("."));
                 return token;
             }
         }
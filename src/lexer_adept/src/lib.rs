@@ -1,12 +1,19 @@
+mod checkpoint;
 mod feed_result;
 mod infinite_iterator;
+mod number;
+mod punct_matcher;
+mod string;
+mod unit_tests;
 
-use crate::feed_result::FeedResult;
-use num_bigint::BigInt;
-use std::{str::FromStr, sync::Arc};
+use crate::{
+    feed_result::FeedResult, number::number_token, punct_matcher::PUNCT_MATCHER,
+    string::string_literal,
+};
+pub use checkpoint::{Checkpoint, LineCheckpoints, LineEdit, Relexed};
 use token::{
-    ALL_DIRECTIVES, ALL_PUNCT_SORTED, Directive, IsTerminated, Punct, StringLiteral, Token,
-    TokenKind,
+    ALL_DIRECTIVES, Directive, IsTerminated, Punct, Radix, StringModifier, Token, TokenKind,
+    is_doc_comment,
 };
 use util_text::{Character, Lexable};
 
@@ -17,13 +24,15 @@ where
 {
     lexable: L,
     state: State<S>,
+    after_dot: bool,
 }
 
 pub enum State<S: Copy> {
     Idle,
     UnaryCall,
     Identifier(String, S),
-    UnknownDirective(String, S),
+    Polymorph(String, S),
+    DirectiveOrLabel(String, S),
     String(StringState<S>),
     Number(NumberState<S>),
     SinglelineComment(SinglelineCommentState<S>),
@@ -32,15 +41,24 @@ pub enum State<S: Copy> {
     EndOfFile(S),
 }
 
+/// The state of the lexer between two tokens, which is all that is needed
+/// to resume lexing from that point
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LexerState {
+    unary_call: bool,
+    after_dot: bool,
+}
+
 pub struct StringState<S: Copy> {
     literal: String,
-    close_char: char,
+    modifier: StringModifier,
     escaped: bool,
     source: S,
 }
 
 pub struct NumberState<S: Copy> {
     literal: String,
+    allow_fraction: bool,
     source: S,
 }
 
@@ -63,9 +81,38 @@ where
         Self {
             lexable,
             state: State::Idle,
+            after_dot: false,
+        }
+    }
+
+    /// Continues lexing from a token boundary previously captured using `snapshot`
+    pub fn resume(lexable: L, state: LexerState) -> Self {
+        Self {
+            lexable,
+            state: if state.unary_call {
+                State::UnaryCall
+            } else {
+                State::Idle
+            },
+            after_dot: state.after_dot,
         }
     }
 
+    /// Captures the current state, which is only possible between tokens
+    /// and before the end of the file is reached
+    pub fn snapshot(&self) -> Option<LexerState> {
+        let unary_call = match self.state {
+            State::Idle => false,
+            State::UnaryCall => true,
+            _ => return None,
+        };
+
+        Some(LexerState {
+            unary_call,
+            after_dot: self.after_dot,
+        })
+    }
+
     fn feed(&mut self) -> FeedResult<Token<S>> {
         match &mut self.state {
             State::Idle => self.feed_idle(),
@@ -93,34 +140,59 @@ where
                 Character::At(c, _) => {
                     string_state.literal.push(c);
 
-                    if c == string_state.close_char && !string_state.escaped {
+                    let modifier = string_state.modifier;
+
+                    if c == modifier.close_char() && !string_state.escaped {
                         let literal = std::mem::take(&mut string_state.literal);
                         let source = string_state.source;
                         self.state = State::Idle;
-                        FeedResult::Has(TokenKind::String(StringLiteral { literal }).at(source))
+                        FeedResult::Has(
+                            TokenKind::String(Box::new(string_literal(literal, modifier, true)))
+                                .at(source),
+                        )
                     } else {
-                        string_state.escaped = !string_state.escaped && c == '\\';
+                        string_state.escaped =
+                            modifier.has_escapes() && !string_state.escaped && c == '\\';
                         FeedResult::Waiting
                     }
                 }
                 Character::End(eof_source) => {
                     let literal = std::mem::take(&mut string_state.literal);
+                    let modifier = string_state.modifier;
                     let source = string_state.source;
                     self.state = State::UnterminatedString(eof_source);
-                    FeedResult::Has(TokenKind::String(StringLiteral { literal }).at(source))
+                    FeedResult::Has(
+                        TokenKind::String(Box::new(string_literal(literal, modifier, false)))
+                            .at(source),
+                    )
                 }
             },
             State::Number(number_state) => {
-                if self.lexable.peek().is_digit() {
-                    let c = self.lexable.next().unwrap().0;
-                    number_state.literal.push(c);
+                let literal = &number_state.literal;
+                let [c, after] = self.lexable.peek_n();
+                let is_decimal = literal.get(..2).and_then(Radix::from_prefix).is_none();
+
+                // Letters are consumed even when invalid, so that errors cover the whole literal
+                let continues = match c.or_nul() {
+                    c if c.is_ascii_alphanumeric() || c == '_' => true,
+                    '+' | '-' => is_decimal && literal.ends_with(['e', 'E']) && after.is_digit(),
+                    '.' => {
+                        is_decimal
+                            && number_state.allow_fraction
+                            && !literal.contains(['.', 'e', 'E'])
+                            && after.is_digit()
+                    }
+                    _ => false,
+                };
+
+                if continues {
+                    number_state.literal.push(self.lexable.next().unwrap().0);
                     FeedResult::Waiting
                 } else {
-                    let big_int = BigInt::from_str(&number_state.literal).unwrap();
                     let literal = std::mem::take(&mut number_state.literal);
                     let source = number_state.source;
                     self.state = State::Idle;
-                    FeedResult::Has(TokenKind::Integer(Arc::new(big_int), literal).at(source))
+                    FeedResult::Has(number_token(literal).at(source))
                 }
             }
             State::UnterminatedString(source) => {
@@ -128,16 +200,37 @@ where
                 self.state = State::Idle;
                 FeedResult::Has(TokenKind::MissingStringTermination.at(source))
             }
-            State::UnknownDirective(directive, source) => {
+            State::Polymorph(name, source) => {
                 if self.lexable.peek().is_identifier_continue() {
-                    directive.push(self.lexable.next().unwrap().0);
+                    name.push(self.lexable.next().unwrap().0);
                     FeedResult::Waiting
                 } else {
-                    let directive = std::mem::take(directive);
+                    let name = std::mem::take(name);
                     let source = *source;
-                    self.state = State::Idle;
+                    self.state = State::UnaryCall;
+                    FeedResult::Has(TokenKind::Polymorph(name).at(source))
+                }
+            }
+            State::DirectiveOrLabel(name, source) => {
+                if self.lexable.peek().is_identifier_continue() {
+                    name.push(self.lexable.next().unwrap().0);
+                    return FeedResult::Waiting;
+                }
+
+                let name = std::mem::take(name);
+                let source = *source;
+                self.state = State::Idle;
+
+                // Labels are enclosed in `@` on both sides, unlike directives
+                if !name.is_empty() && self.lexable.eat('@') {
+                    FeedResult::Has(TokenKind::Label(name).at(source))
+                } else if ALL_DIRECTIVES.contains(&name.as_str()) {
+                    FeedResult::Has(
+                        TokenKind::Directive(Directive::Standard(name.into())).at(source),
+                    )
+                } else {
                     FeedResult::Has(
-                        TokenKind::Directive(Directive::Unknown(directive.into())).at(source),
+                        TokenKind::Directive(Directive::Unknown(name.into())).at(source),
                     )
                 }
             }
@@ -146,7 +239,7 @@ where
                     let content = std::mem::take(&mut state.content);
                     let source = state.source;
                     self.state = State::Idle;
-                    FeedResult::Has(TokenKind::SinglelineComment(content).at(source))
+                    FeedResult::Has(comment(content, IsTerminated::Terminated).at(source))
                 } else {
                     state.content.push(self.lexable.next().unwrap().0);
                     FeedResult::Waiting
@@ -155,13 +248,12 @@ where
             State::MultilineComment(state) => {
                 const MULTILINE_COMMENT_END: &str = "*/";
 
-                if let Ok(source) = self.lexable.eat_remember(MULTILINE_COMMENT_END) {
+                if self.lexable.eat(MULTILINE_COMMENT_END) {
                     let mut content = std::mem::take(&mut state.content);
+                    let source = state.source;
                     self.state = State::Idle;
                     content.push_str(MULTILINE_COMMENT_END);
-                    FeedResult::Has(
-                        TokenKind::MultilineComment(content, IsTerminated::Terminated).at(source),
-                    )
+                    FeedResult::Has(comment(content, IsTerminated::Terminated).at(source))
                 } else {
                     match self.lexable.next() {
                         Character::At(c, _) => {
@@ -172,10 +264,7 @@ where
                             let content = std::mem::take(&mut state.content);
                             let source = state.source;
                             self.state = State::Idle;
-                            FeedResult::Has(
-                                TokenKind::MultilineComment(content, IsTerminated::Unterminated)
-                                    .at(source),
-                            )
+                            FeedResult::Has(comment(content, IsTerminated::Unterminated).at(source))
                         }
                     }
                 }
@@ -185,87 +274,122 @@ where
     }
 
     fn feed_idle(&mut self) -> FeedResult<Token<S>> {
-        if let Some((spacing_atom, source)) = self.lexable.eat_column_spacing_atom() {
-            return FeedResult::Has(TokenKind::ColumnSpacing(spacing_atom).at(source));
-        }
-
-        const SINGLELINE_COMMENT_START: &str = "//";
-        if let Ok(source) = self.lexable.eat_remember(SINGLELINE_COMMENT_START) {
-            self.state = State::SinglelineComment(SinglelineCommentState {
-                content: SINGLELINE_COMMENT_START.into(),
-                source: source,
-            });
-            return FeedResult::Waiting;
-        }
+        // Dispatching on the first character means that only the kind of token
+        // that can start with it is attempted
+        let (c, source) = match self.lexable.peek() {
+            Character::At(c, source) => (c, source),
+            Character::End(source) => {
+                self.state = State::EndOfFile(source);
+                return FeedResult::Waiting;
+            }
+        };
 
-        const MULTILINE_COMMENT_START: &str = "/*";
-        if let Ok(source) = self.lexable.eat_remember(MULTILINE_COMMENT_START) {
-            self.state = State::MultilineComment(MultilineCommentState {
-                content: MULTILINE_COMMENT_START.into(),
-                source: source,
+        if let Some(modifier) = StringModifier::from_prefix(c)
+            && self.lexable.peek_nth(1).is('"')
+        {
+            self.lexable.next();
+            self.lexable.next();
+            self.state = State::String(StringState {
+                literal: format!("{c}\""),
+                modifier,
+                escaped: false,
+                source,
             });
             return FeedResult::Waiting;
         }
 
-        for punct_str in [")", "]", "}"] {
-            if let Ok(source) = self.lexable.eat_remember(punct_str) {
-                self.state = State::UnaryCall;
-                return FeedResult::Has(TokenKind::Punct(Punct::new(punct_str)).at(source));
+        match c {
+            ' ' | '\t' => {
+                let (spacing_atom, source) = self.lexable.eat_column_spacing_atom().unwrap();
+                FeedResult::Has(TokenKind::ColumnSpacing(spacing_atom).at(source))
             }
-        }
-
-        if let Some((atom, source)) = self.lexable.eat_line_spacing_atom() {
-            return FeedResult::Has(TokenKind::LineSpacing(atom).at(source));
-        }
-
-        if self.lexable.peek().is_identifier_start() {
-            self.state = State::Identifier("".into(), self.lexable.peek().source());
-            return FeedResult::Waiting;
-        }
-
-        for punct_str in ALL_PUNCT_SORTED.iter().copied() {
-            if let Ok(source) = self.lexable.eat_remember(punct_str) {
-                return FeedResult::Has(TokenKind::Punct(Punct::new(punct_str)).at(source));
+            '\n' => {
+                let (atom, source) = self.lexable.eat_line_spacing_atom().unwrap();
+                FeedResult::Has(TokenKind::LineSpacing(atom).at(source))
             }
-        }
-
-        if let Ok(source) = self.lexable.eat_remember("@") {
-            for possible in ALL_DIRECTIVES.iter().copied() {
-                if self.lexable.eat(possible) {
-                    return FeedResult::Has(
-                        TokenKind::Directive(Directive::Standard(possible.into())).at(source),
-                    );
-                }
+            '/' if self.lexable.peek_starts_with("//") => {
+                const SINGLELINE_COMMENT_START: &str = "//";
+                self.lexable.eat(SINGLELINE_COMMENT_START);
+                self.state = State::SinglelineComment(SinglelineCommentState {
+                    content: SINGLELINE_COMMENT_START.into(),
+                    source,
+                });
+                FeedResult::Waiting
             }
+            '/' if self.lexable.peek_starts_with("/*") => {
+                const MULTILINE_COMMENT_START: &str = "/*";
+                self.lexable.eat(MULTILINE_COMMENT_START);
+                self.state = State::MultilineComment(MultilineCommentState {
+                    content: MULTILINE_COMMENT_START.into(),
+                    source,
+                });
+                FeedResult::Waiting
+            }
+            '"' | '\'' => {
+                self.lexable.next();
 
-            self.state = State::UnknownDirective("".into(), source);
-            return FeedResult::Waiting;
-        }
-
-        if self.lexable.peek().is_digit() {
-            let source = self.lexable.peek().source();
-            self.state = State::Number(NumberState {
-                literal: String::new(),
-                source,
-            });
-            return FeedResult::Waiting;
-        }
+                // After a name or closing bracket, `'` is lexed as an operator instead
+                let modifier = if c == '"' {
+                    StringModifier::Normal
+                } else {
+                    StringModifier::Character
+                };
 
-        match self.lexable.next() {
-            Character::At(c @ ('"' | '\''), source) => {
                 self.state = State::String(StringState {
                     literal: c.into(),
-                    close_char: c,
+                    modifier,
                     escaped: false,
                     source,
                 });
                 FeedResult::Waiting
             }
-            Character::At(c, source) => FeedResult::Has(TokenKind::Error(c).at(source)),
-            Character::End(source) => {
-                self.state = State::EndOfFile(source);
+            '@' => {
+                self.lexable.next();
+                self.state = State::DirectiveOrLabel("".into(), source);
+                FeedResult::Waiting
+            }
+            '$' if self.lexable.peek_nth(1).is_identifier_start() => {
+                self.lexable.next();
+                self.state = State::Polymorph("".into(), source);
+                FeedResult::Waiting
+            }
+            '0'..='9' => {
+                self.state = State::Number(NumberState {
+                    literal: String::new(),
+                    // Allows `pair.0.1` to mean nested element access
+                    allow_fraction: !self.after_dot,
+                    source,
+                });
+                FeedResult::Waiting
+            }
+            _ if self.lexable.peek().is_identifier_start() => {
+                self.state = State::Identifier("".into(), source);
                 FeedResult::Waiting
             }
+            _ => {
+                let Some((punct, source)) = PUNCT_MATCHER.eat(&mut self.lexable) else {
+                    self.lexable.next();
+                    return FeedResult::Has(TokenKind::Error(c).at(source));
+                };
+
+                if punct.is_any(&[")", "]", "}"]) {
+                    self.state = State::UnaryCall;
+                }
+
+                FeedResult::Has(TokenKind::Punct(punct).at(source))
+            }
         }
     }
 }
+
+/// Doc comments are lexed separately from other comments, so that the parser
+/// can attach them to the binding that follows
+fn comment(content: String, is_terminated: IsTerminated) -> TokenKind {
+    if is_doc_comment(&content) {
+        TokenKind::DocComment(content, is_terminated)
+    } else if content.starts_with("//") {
+        TokenKind::SinglelineComment(content)
+    } else {
+        TokenKind::MultilineComment(content, is_terminated)
+    }
+}
@@ -0,0 +1,89 @@
+use num_bigint::BigInt;
+use std::sync::Arc;
+use token::{NumberError, Radix, TokenKind};
+
+/// Converts the text of a numeric literal into an integer or float token
+pub fn number_token(text: String) -> TokenKind {
+    match parse_number(&text) {
+        Ok(Number::Integer(value)) => TokenKind::Integer(Arc::new(value), text),
+        Ok(Number::Float(value)) => TokenKind::Float(value, text),
+        Err(error) => TokenKind::MalformedNumber(text, error),
+    }
+}
+
+enum Number {
+    Integer(BigInt),
+    Float(f64),
+}
+
+fn parse_number(text: &str) -> Result<Number, NumberError> {
+    if let Some(radix) = text.get(..2).and_then(Radix::from_prefix) {
+        let digits = digits(&text[2..], radix)?;
+
+        if digits.is_empty() {
+            return Err(NumberError::MissingDigits(radix));
+        }
+
+        return Ok(Number::Integer(
+            BigInt::parse_bytes(digits.as_bytes(), radix.value()).unwrap(),
+        ));
+    }
+
+    let (mantissa, exponent) = match text.find(['e', 'E']) {
+        Some(index) => (&text[..index], Some(&text[index + 1..])),
+        None => (text, None),
+    };
+
+    let (whole, fraction) = match mantissa.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (mantissa, None),
+    };
+
+    let whole = digits(whole, Radix::Decimal)?;
+    let fraction = fraction
+        .map(|fraction| digits(fraction, Radix::Decimal))
+        .transpose()?;
+
+    let exponent = exponent
+        .map(|exponent| {
+            let (sign, exponent) = match exponent.strip_prefix(['+', '-']) {
+                Some(rest) => (&exponent[..1], rest),
+                None => ("", exponent),
+            };
+
+            let exponent = digits(exponent, Radix::Decimal)?;
+            if exponent.is_empty() {
+                return Err(NumberError::MissingExponentDigits);
+            }
+            Ok(format!("{sign}{exponent}"))
+        })
+        .transpose()?;
+
+    if fraction.is_none() && exponent.is_none() {
+        return Ok(Number::Integer(
+            BigInt::parse_bytes(whole.as_bytes(), 10).unwrap(),
+        ));
+    }
+
+    let normalized = format!(
+        "{}.{}e{}",
+        whole,
+        fraction.as_deref().unwrap_or("0"),
+        exponent.as_deref().unwrap_or("0")
+    );
+    Ok(Number::Float(normalized.parse().unwrap()))
+}
+
+/// Removes digit separators, and ensures that all remaining characters are valid digits
+fn digits(text: &str, radix: Radix) -> Result<String, NumberError> {
+    text.chars()
+        .filter(|c| *c != '_')
+        .map(|c| {
+            if c.is_digit(radix.value()) {
+                Ok(c)
+            } else {
+                Err(NumberError::InvalidDigit(c, radix))
+            }
+        })
+        .collect()
+}
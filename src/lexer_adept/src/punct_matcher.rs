@@ -0,0 +1,87 @@
+use std::sync::LazyLock;
+use token::{ALL_PUNCT_SORTED, Punct};
+use util_text::{Character, Lexable};
+
+/// Every punctuator is ASCII, so each state only needs a transition per ASCII character
+const ASCII: usize = 128;
+
+/// State 0 is the start state, so it doubles as "no transition"
+const NO_STATE: u8 = 0;
+
+/// A DFA over the characters of all punctuators, which finds the longest
+/// punctuator at the start of the input using a single table lookup per character
+pub struct PunctMatcher {
+    transitions: Vec<[u8; ASCII]>,
+    accepts: Vec<Option<&'static str>>,
+}
+
+pub static PUNCT_MATCHER: LazyLock<PunctMatcher> =
+    LazyLock::new(|| PunctMatcher::new(*ALL_PUNCT_SORTED));
+
+impl PunctMatcher {
+    fn new(puncts: &[&'static str]) -> Self {
+        let mut matcher = Self {
+            transitions: vec![[NO_STATE; ASCII]],
+            accepts: vec![None],
+        };
+
+        for punct in puncts.iter().copied() {
+            let mut state = 0;
+
+            for byte in punct.bytes() {
+                let next = matcher.transitions[state][byte as usize];
+
+                state = if next != NO_STATE {
+                    next as usize
+                } else {
+                    let next = matcher.transitions.len();
+                    matcher.transitions[state][byte as usize] =
+                        u8::try_from(next).expect("too many punctuator states");
+                    matcher.transitions.push([NO_STATE; ASCII]);
+                    matcher.accepts.push(None);
+                    next
+                };
+            }
+
+            matcher.accepts[state] = Some(punct);
+        }
+
+        matcher
+    }
+
+    pub fn eat<S: Copy>(&self, lexable: &mut impl Lexable<S>) -> Option<(Punct, S)> {
+        let source = lexable.peek().source();
+        let mut state = 0;
+        let mut longest = None;
+
+        for i in 0.. {
+            let Character::At(c, _) = lexable.peek_nth(i) else {
+                break;
+            };
+
+            let next = if c.is_ascii() {
+                self.transitions[state][c as usize]
+            } else {
+                NO_STATE
+            };
+
+            if next == NO_STATE {
+                break;
+            }
+
+            state = next as usize;
+
+            if let Some(punct) = self.accepts[state] {
+                longest = Some((punct, i + 1));
+            }
+        }
+
+        let (punct, len) = longest?;
+
+        for _ in 0..len {
+            lexable.next();
+        }
+
+        Some((Punct::new(punct), source))
+    }
+}
@@ -0,0 +1,161 @@
+use std::{iter::Peekable, ops::Range, str::CharIndices};
+use token::{StringError, StringErrorKind, StringLiteral, StringModifier};
+
+/// Decodes the escape sequences of a string or character literal
+pub fn string_literal(
+    literal: String,
+    modifier: StringModifier,
+    terminated: bool,
+) -> StringLiteral {
+    let start = modifier.prefix().len() + 1;
+    let end = if terminated {
+        literal.len() - 1
+    } else {
+        literal.len()
+    };
+
+    let mut decoder = Decoder {
+        content: &literal[start..end],
+        offset: start,
+        modifier,
+        value: Vec::new(),
+        errors: Vec::new(),
+    };
+    decoder.run();
+
+    let Decoder {
+        value, mut errors, ..
+    } = decoder;
+
+    if modifier == StringModifier::Character
+        && errors.is_empty()
+        && std::str::from_utf8(&value).map_or(true, |value| value.chars().count() != 1)
+    {
+        errors.push(StringError {
+            range: 0..literal.len(),
+            kind: StringErrorKind::NotOneCharacter,
+        });
+    }
+
+    StringLiteral {
+        literal,
+        modifier,
+        value,
+        errors,
+    }
+}
+
+struct Decoder<'a> {
+    content: &'a str,
+    offset: usize,
+    modifier: StringModifier,
+    value: Vec<u8>,
+    errors: Vec<StringError>,
+}
+
+impl<'a> Decoder<'a> {
+    fn run(&mut self) {
+        let mut chars = self.content.char_indices().peekable();
+
+        while let Some((i, c)) = chars.next() {
+            if c != '\\' || !self.modifier.has_escapes() {
+                if self.modifier == StringModifier::ByteString && !c.is_ascii() {
+                    self.error(i..i + c.len_utf8(), StringErrorKind::NonAsciiInByteString);
+                } else {
+                    self.push_char(i..i + c.len_utf8(), c);
+                }
+                continue;
+            }
+
+            let Some((_, escape)) = chars.next() else {
+                self.error(i..i + 1, StringErrorKind::MissingEscape);
+                break;
+            };
+
+            match escape {
+                'n' => self.push_char(i..i + 2, '\n'),
+                'r' => self.push_char(i..i + 2, '\r'),
+                't' => self.push_char(i..i + 2, '\t'),
+                '0' => self.push_char(i..i + 2, '\0'),
+                '\\' | '\'' | '"' => self.push_char(i..i + 2, escape),
+                'x' => self.hex_escape(i, &mut chars),
+                'u' => self.unicode_escape(i, &mut chars),
+                _ => self.error(
+                    i..i + 1 + escape.len_utf8(),
+                    StringErrorKind::UnknownEscape(escape),
+                ),
+            }
+        }
+    }
+
+    fn hex_escape(&mut self, start: usize, chars: &mut Peekable<CharIndices>) {
+        let digits = [(); 2].map(|_| chars.next_if(|(_, c)| c.is_ascii_hexdigit()));
+        let range = start..self.consumed(chars);
+
+        let [Some((_, high)), Some((_, low))] = digits else {
+            self.error(range, StringErrorKind::InvalidHexEscape);
+            return;
+        };
+
+        let byte = (high.to_digit(16).unwrap() * 16 + low.to_digit(16).unwrap()) as u8;
+
+        if self.modifier == StringModifier::ByteString {
+            self.push_byte(range, byte);
+        } else if byte.is_ascii() {
+            self.push_char(range, byte as char);
+        } else {
+            self.error(range, StringErrorKind::HexEscapeOutOfRange);
+        }
+    }
+
+    fn unicode_escape(&mut self, start: usize, chars: &mut Peekable<CharIndices>) {
+        let open = chars.next_if(|(_, c)| *c == '{').is_some();
+        let mut digits = String::new();
+        while let Some((_, digit)) = chars.next_if(|(_, c)| open && c.is_ascii_hexdigit()) {
+            digits.push(digit);
+        }
+        let close = open && chars.next_if(|(_, c)| *c == '}').is_some();
+        let range = start..self.consumed(chars);
+
+        if self.modifier == StringModifier::ByteString {
+            self.error(range, StringErrorKind::UnicodeEscapeInByteString);
+            return;
+        }
+
+        let c = (close && (1..=6).contains(&digits.len()))
+            .then(|| u32::from_str_radix(&digits, 16).ok())
+            .flatten()
+            .and_then(char::from_u32);
+
+        match c {
+            Some(c) => self.push_char(range, c),
+            None => self.error(range, StringErrorKind::InvalidUnicodeEscape),
+        }
+    }
+
+    fn consumed(&self, chars: &mut Peekable<CharIndices>) -> usize {
+        chars.peek().map_or(self.content.len(), |(i, _)| *i)
+    }
+
+    fn push_char(&mut self, range: Range<usize>, c: char) {
+        let mut buffer = [0; 4];
+        for byte in c.encode_utf8(&mut buffer).bytes() {
+            self.push_byte(range.clone(), byte);
+        }
+    }
+
+    fn push_byte(&mut self, range: Range<usize>, byte: u8) {
+        if self.modifier == StringModifier::CString && byte == 0 {
+            self.error(range, StringErrorKind::NulInCString);
+        } else {
+            self.value.push(byte);
+        }
+    }
+
+    fn error(&mut self, range: Range<usize>, kind: StringErrorKind) {
+        self.errors.push(StringError {
+            range: range.start + self.offset..range.end + self.offset,
+            kind,
+        });
+    }
+}
@@ -0,0 +1,584 @@
+#![cfg(test)]
+
+use crate::{Lexer, LineCheckpoints, LineEdit, punct_matcher::PUNCT_MATCHER};
+use std::sync::Arc;
+use text_edit::SourcePosition;
+use token::{
+    ALL_PUNCT_SORTED, Directive, IsTerminated, NumberError, Punct, Radix, StringError,
+    StringErrorKind, StringLiteral, StringModifier, Token, TokenKind,
+};
+use util_infinite_iterator::InfiniteIterator;
+use util_text::{CharacterPeeker, ColumnSpacingAtom, Lexable, LineSpacingAtom, Positioned};
+
+type Spanned = (TokenKind, SourcePosition, SourcePosition);
+
+fn spanned(token: Token<SourcePosition>) -> Spanned {
+    (token.kind, token.source, token.end)
+}
+
+fn lex_all<L>(mut lexer: Lexer<L, SourcePosition>) -> Vec<Spanned>
+where
+    L: util_text::Lexable<SourcePosition> + Send,
+{
+    let mut tokens = vec![];
+
+    loop {
+        let token = lexer.next();
+        let end_of_file = token.kind.is_end_of_file();
+        tokens.push(spanned(token));
+
+        if end_of_file {
+            return tokens;
+        }
+    }
+}
+
+fn lex(content: &str) -> Vec<Spanned> {
+    lex_all(Lexer::new(CharacterPeeker::new(Positioned::new(
+        content.chars(),
+    ))))
+}
+
+/// The kind of each token, without the end of the file
+fn kinds(content: &str) -> Vec<TokenKind> {
+    lex(content)
+        .into_iter()
+        .map(|(kind, _, _)| kind)
+        .filter(|kind| !kind.is_end_of_file())
+        .collect()
+}
+
+fn integer(value: u64, text: &str) -> TokenKind {
+    TokenKind::Integer(Arc::new(value.into()), text.into())
+}
+
+fn punct(punct: &'static str) -> TokenKind {
+    TokenKind::Punct(Punct::new(punct))
+}
+
+fn identifier(name: &str) -> TokenKind {
+    TokenKind::Identifier(name.into())
+}
+
+/// Relexes `after` from the checkpoint that `before` recorded for `line`, which must
+/// give the same tokens as lexing all of `after` from that point on
+fn assert_relex_matches(before: &str, after: &str, line: usize) {
+    let checkpoints = LineCheckpoints::record(before);
+    let resume_at = checkpoints.get(line).position.byte;
+
+    let relexed = lex_all(checkpoints.resume(after, line));
+    let expected = lex(after)
+        .into_iter()
+        .filter(|(_, source, _)| source.byte >= resume_at)
+        .collect::<Vec<_>>();
+
+    assert_eq!(relexed, expected);
+}
+
+#[test]
+fn checkpoints_inside_multiline_comment_resume_at_comment() {
+    let content = "a = 1\n/* first\nsecond\nthird */\nb = 2\n";
+    let checkpoints = LineCheckpoints::record(content);
+
+    let comment_start = content.find("/*").unwrap();
+    assert_eq!(checkpoints.get(2).position.byte, comment_start);
+    assert_eq!(checkpoints.get(3).position.byte, comment_start);
+    assert_eq!(
+        checkpoints.get(4).position.byte,
+        content.find("b = 2").unwrap()
+    );
+}
+
+#[test]
+fn relex_after_edit_inside_multiline_comment() {
+    let before = "a = 1\n/* first\nsecond\nthird */\nb = 2\n";
+
+    // Changing the text of the comment
+    assert_relex_matches(before, "a = 1\n/* first\nchanged\nthird */\nb = 2\n", 2);
+
+    // Closing the comment early, so the rest of it becomes code
+    assert_relex_matches(before, "a = 1\n/* first\n*/ second\nthird */\nb = 2\n", 2);
+
+    // Adding lines to the comment
+    assert_relex_matches(
+        before,
+        "a = 1\n/* first\nsecond\nmore\nlines\nthird */\nb = 2\n",
+        3,
+    );
+}
+
+#[test]
+fn relex_after_edit_inside_multiline_string() {
+    let before = "a = \"first\nsecond\nthird\"\nb = 2\n";
+    assert_eq!(
+        LineCheckpoints::record(before).get(1).position.byte,
+        before.find('"').unwrap()
+    );
+
+    assert_relex_matches(before, "a = \"first\nchanged\nthird\"\nb = 2\n", 1);
+
+    // Ending the string early, so the rest of it becomes code
+    assert_relex_matches(before, "a = \"first\nsec\"ond\nthird\"\nb = 2\n", 1);
+}
+
+#[test]
+fn relex_after_edit_opening_multiline_comment() {
+    let before = "a = 1\nb = 2\nc = 3\n";
+
+    assert_relex_matches(before, "a = 1\n/* b = 2\nc = 3\n", 1);
+    assert_relex_matches(before, "a = 1\nb = 2 /* unterminated\nc = 3\n", 1);
+}
+
+/// Relexes `after` using the checkpoints and tokens of `before`, which must give the
+/// same tokens and checkpoints as lexing all of `after`
+fn assert_relex_with_tokens(before: &str, after: &str, edit: LineEdit) {
+    let before_tokens = lex_tokens(before);
+    let relexed = LineCheckpoints::record(before).relex(&before_tokens, after, edit);
+
+    let tokens = relexed.tokens.into_iter().map(spanned).collect::<Vec<_>>();
+    assert_eq!(tokens, lex(after));
+
+    let expected = LineCheckpoints::record(after);
+    for line in 0..after.lines().count() + 1 {
+        assert_eq!(
+            relexed.checkpoints.get(line),
+            expected.get(line),
+            "line {line}"
+        );
+    }
+}
+
+fn lex_tokens(content: &str) -> Vec<Token<SourcePosition>> {
+    let mut lexer = Lexer::new(CharacterPeeker::new(Positioned::new(content.chars())));
+    let mut tokens = vec![];
+
+    loop {
+        let token = lexer.next();
+        let end_of_file = token.kind.is_end_of_file();
+        tokens.push(token);
+
+        if end_of_file {
+            return tokens;
+        }
+    }
+}
+
+#[test]
+fn relex_keeps_tokens_after_edited_line() {
+    let before = "a = 1\nb = 2\nc = 3\nd = 4\n";
+
+    let edit = LineEdit {
+        start: 1,
+        old_end: 1,
+        new_end: 1,
+    };
+    assert_relex_with_tokens(before, "a = 1\nb = 22\nc = 3\nd = 4\n", edit);
+
+    // Adding lines shifts the kept tokens down
+    let edit = LineEdit {
+        start: 1,
+        old_end: 1,
+        new_end: 3,
+    };
+    assert_relex_with_tokens(before, "a = 1\nb = 2\nx = 5\ny = 6\nc = 3\nd = 4\n", edit);
+
+    // Removing lines shifts the kept tokens up
+    let edit = LineEdit {
+        start: 1,
+        old_end: 2,
+        new_end: 1,
+    };
+    assert_relex_with_tokens(before, "a = 1\nb = 3\nd = 4\n", edit);
+}
+
+#[test]
+fn relex_until_end_when_state_never_resynchronizes() {
+    let before = "a = 1\nb = 2\nc = 3\n";
+    let edit = LineEdit {
+        start: 1,
+        old_end: 1,
+        new_end: 1,
+    };
+
+    assert_relex_with_tokens(before, "a = 1\n/* b = 2\nc = 3\n", edit);
+    assert_relex_with_tokens(before, "a = 1\nb = \"2\nc = 3\n", edit);
+}
+
+/// The punctuator and its length in bytes that the DFA matches at the start of `input`
+fn dfa_match(input: &str) -> Option<(String, usize)> {
+    let mut lexable = CharacterPeeker::new(Positioned::new(input.chars()));
+    let (punct, _) = PUNCT_MATCHER.eat(&mut lexable)?;
+    Some((punct.to_string(), lexable.peek().source().byte))
+}
+
+/// Trying each punctuator from longest to shortest, which is how punctuation used to be lexed
+fn longest_match(input: &str) -> Option<(String, usize)> {
+    ALL_PUNCT_SORTED
+        .iter()
+        .copied()
+        .find(|punct| input.starts_with(punct))
+        .map(|punct| (punct.to_string(), punct.len()))
+}
+
+#[test]
+fn punct_dfa_matches_longest_match() {
+    let mut inputs = vec![];
+
+    for punct in ALL_PUNCT_SORTED.iter() {
+        inputs.push(punct.to_string());
+        inputs.push(format!("{punct} "));
+        inputs.push(format!("{punct}x"));
+        inputs.push(format!("{punct}é"));
+
+        for next in ALL_PUNCT_SORTED.iter() {
+            inputs.push(format!("{punct}{next}"));
+        }
+    }
+
+    for input in inputs {
+        assert_eq!(dfa_match(&input), longest_match(&input), "for {input:?}");
+    }
+}
+
+#[test]
+fn punct_dfa_rejects_non_punctuation() {
+    for input in ["", " ", "x", "é", "\"", "0"] {
+        assert_eq!(dfa_match(input), None, "for {input:?}");
+    }
+}
+
+#[test]
+fn floats() {
+    assert_eq!(kinds("1.5"), [TokenKind::Float(1.5, "1.5".into())]);
+    assert_eq!(kinds("1e10"), [TokenKind::Float(1e10, "1e10".into())]);
+    assert_eq!(kinds("1.5e-3"), [TokenKind::Float(1.5e-3, "1.5e-3".into())]);
+    assert_eq!(kinds("2E+4"), [TokenKind::Float(2e4, "2E+4".into())]);
+    assert_eq!(
+        kinds("1_000.25"),
+        [TokenKind::Float(1000.25, "1_000.25".into())]
+    );
+}
+
+#[test]
+fn radix_integers() {
+    assert_eq!(kinds("0x1F"), [integer(31, "0x1F")]);
+    assert_eq!(kinds("0o17"), [integer(15, "0o17")]);
+    assert_eq!(kinds("0b1_0"), [integer(2, "0b1_0")]);
+    assert_eq!(kinds("1_000_"), [integer(1000, "1_000_")]);
+}
+
+#[test]
+fn malformed_numbers() {
+    let malformed = |text: &str, error| TokenKind::MalformedNumber(text.into(), error);
+
+    // Invalid letters are part of the literal, so the error covers all of it
+    assert_eq!(
+        kinds("0x1F_u"),
+        [malformed(
+            "0x1F_u",
+            NumberError::InvalidDigit('u', Radix::Hexadecimal)
+        )]
+    );
+    assert_eq!(
+        kinds("0b102"),
+        [malformed(
+            "0b102",
+            NumberError::InvalidDigit('2', Radix::Binary)
+        )]
+    );
+    assert_eq!(
+        kinds("0b"),
+        [malformed("0b", NumberError::MissingDigits(Radix::Binary))]
+    );
+    assert_eq!(
+        kinds("0x"),
+        [malformed(
+            "0x",
+            NumberError::MissingDigits(Radix::Hexadecimal)
+        )]
+    );
+    assert_eq!(
+        kinds("1e"),
+        [malformed("1e", NumberError::MissingExponentDigits)]
+    );
+
+    // A sign is only part of the exponent when digits follow it
+    assert_eq!(
+        kinds("1e+"),
+        [
+            malformed("1e", NumberError::MissingExponentDigits),
+            punct("+")
+        ]
+    );
+}
+
+#[test]
+fn fractions_need_digits_and_not_follow_dots() {
+    assert_eq!(
+        kinds("1.foo"),
+        [integer(1, "1"), punct("."), identifier("foo")]
+    );
+    assert_eq!(
+        kinds("1..2"),
+        [integer(1, "1"), punct("."), punct("."), integer(2, "2")]
+    );
+
+    // Nested element access
+    assert_eq!(
+        kinds("x.0.1"),
+        [
+            identifier("x"),
+            punct("."),
+            integer(0, "0"),
+            punct("."),
+            integer(1, "1"),
+        ]
+    );
+}
+
+/// The single string literal that `content` lexes to
+fn string(content: &str) -> StringLiteral {
+    match kinds(content).as_slice() {
+        [TokenKind::String(literal)] => *literal.clone(),
+        other => panic!("expected a string literal, got {other:?}"),
+    }
+}
+
+fn string_errors(content: &str) -> Vec<StringErrorKind> {
+    string(content)
+        .errors
+        .into_iter()
+        .map(|error| error.kind)
+        .collect()
+}
+
+#[test]
+fn string_escapes() {
+    let literal = string(r#""a\n\t\\\"\x41\u{1F600}""#);
+
+    assert_eq!(literal.modifier, StringModifier::Normal);
+    assert_eq!(literal.as_str(), Some("a\n\t\\\"A\u{1F600}"));
+    assert_eq!(literal.errors, []);
+}
+
+#[test]
+fn invalid_string_escapes() {
+    assert_eq!(
+        string(r#""\q""#).errors,
+        [StringError {
+            range: 1..3,
+            kind: StringErrorKind::UnknownEscape('q'),
+        }]
+    );
+    assert_eq!(
+        string_errors(r#""\u{110000}""#),
+        [StringErrorKind::InvalidUnicodeEscape]
+    );
+    assert_eq!(
+        string_errors(r#""\u{}""#),
+        [StringErrorKind::InvalidUnicodeEscape]
+    );
+    assert_eq!(
+        string_errors(r#""\x4""#),
+        [StringErrorKind::InvalidHexEscape]
+    );
+    assert_eq!(
+        string_errors(r#""\x80""#),
+        [StringErrorKind::HexEscapeOutOfRange]
+    );
+}
+
+#[test]
+fn string_modifiers() {
+    let c_string = string(r#"c"hi""#);
+    assert_eq!(c_string.modifier, StringModifier::CString);
+    assert_eq!(c_string.value, b"hi");
+    assert_eq!(string_errors(r#"c"a\0""#), [StringErrorKind::NulInCString]);
+
+    // Raw strings keep backslashes as written
+    let raw = string(r#"r"a\n""#);
+    assert_eq!(raw.modifier, StringModifier::RawString);
+    assert_eq!(raw.as_str(), Some(r"a\n"));
+
+    let bytes = string(r#"b"ab\xFF""#);
+    assert_eq!(bytes.modifier, StringModifier::ByteString);
+    assert_eq!(bytes.value, b"ab\xFF");
+    assert_eq!(
+        string_errors(r#"b"\u{41}""#),
+        [StringErrorKind::UnicodeEscapeInByteString]
+    );
+    assert_eq!(
+        string_errors("b\"\u{e9}\""),
+        [StringErrorKind::NonAsciiInByteString]
+    );
+
+    // Other prefixes are identifiers followed by a string
+    assert!(matches!(
+        kinds(r#"x"a""#).as_slice(),
+        [TokenKind::Identifier(_), TokenKind::String(_)]
+    ));
+}
+
+#[test]
+fn character_literals() {
+    let character = string("'x'");
+    assert_eq!(character.modifier, StringModifier::Character);
+    assert_eq!(character.as_str(), Some("x"));
+
+    assert_eq!(string(r"'\n'").as_str(), Some("\n"));
+    assert_eq!(string_errors("'xy'"), [StringErrorKind::NotOneCharacter]);
+}
+
+#[test]
+fn quote_after_callee_is_unary_call() {
+    assert_eq!(kinds("f'x"), [identifier("f"), punct("'"), identifier("x")]);
+    assert_eq!(
+        kinds("(a)'x"),
+        [
+            punct("("),
+            identifier("a"),
+            punct(")"),
+            punct("'"),
+            identifier("x")
+        ]
+    );
+
+    // Only directly after a callee, otherwise it starts a character literal
+    assert!(matches!(
+        kinds("f 'x'").as_slice(),
+        [
+            TokenKind::Identifier(_),
+            TokenKind::ColumnSpacing(_),
+            TokenKind::String(_)
+        ]
+    ));
+}
+
+#[test]
+fn polymorphs() {
+    assert_eq!(kinds("$T"), [TokenKind::Polymorph("T".into())]);
+    assert_eq!(
+        kinds("$Elem,"),
+        [TokenKind::Polymorph("Elem".into()), punct(",")]
+    );
+
+    // Without a name, `$` is only punctuation
+    assert_eq!(kinds("$"), [punct("$")]);
+    assert_eq!(kinds("$1"), [punct("$"), integer(1, "1")]);
+}
+
+#[test]
+fn labels_and_directives() {
+    assert_eq!(kinds("@outer@"), [TokenKind::Label("outer".into())]);
+    assert_eq!(
+        kinds("@if"),
+        [TokenKind::Directive(Directive::Standard("if".into()))]
+    );
+    assert_eq!(
+        kinds("@unknown"),
+        [TokenKind::Directive(Directive::Unknown("unknown".into()))]
+    );
+    assert_eq!(
+        kinds("@outer@ @fn"),
+        [
+            TokenKind::Label("outer".into()),
+            TokenKind::ColumnSpacing(ColumnSpacingAtom::Spaces(1)),
+            TokenKind::Directive(Directive::Standard("fn".into())),
+        ]
+    );
+}
+
+/// The byte range and the UTF-16 line and column range of each token
+fn spans(content: &str) -> Vec<(String, (usize, usize), (usize, usize), (usize, usize))> {
+    lex(content)
+        .into_iter()
+        .map(|(kind, start, end)| {
+            (
+                kind.to_string(),
+                (start.byte, end.byte),
+                (start.point.line.0, start.point.col.0),
+                (end.point.line.0, end.point.col.0),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn token_spans() {
+    let span = |text: &str, bytes, start, end| (text.to_string(), bytes, start, end);
+
+    assert_eq!(
+        spans("a :: \"\u{1F600}\"\n  bc"),
+        [
+            span("a", (0, 1), (0, 0), (0, 1)),
+            span(" ", (1, 2), (0, 1), (0, 2)),
+            span("::", (2, 4), (0, 2), (0, 4)),
+            span(" ", (4, 5), (0, 4), (0, 5)),
+            // The emoji is four bytes, but two UTF-16 code units
+            span("\"\u{1F600}\"", (5, 11), (0, 5), (0, 9)),
+            span("\n", (11, 12), (0, 9), (1, 0)),
+            span("  ", (12, 14), (1, 0), (1, 2)),
+            span("bc", (14, 16), (1, 2), (1, 4)),
+            span("", (16, 16), (1, 4), (1, 4)),
+        ]
+    );
+}
+
+#[test]
+fn multiline_token_spans() {
+    assert_eq!(
+        spans("/* a\nb */x"),
+        [
+            ("/* a\nb */".into(), (0, 9), (0, 0), (1, 4)),
+            ("x".into(), (9, 10), (1, 4), (1, 5)),
+            ("".into(), (10, 10), (1, 5), (1, 5)),
+        ]
+    );
+}
+
+#[test]
+fn doc_comments() {
+    let doc = |text: &str| TokenKind::DocComment(text.into(), IsTerminated::Terminated);
+
+    assert_eq!(kinds("/// Adds"), [doc("/// Adds")]);
+    assert_eq!(kinds("/** Adds */"), [doc("/** Adds */")]);
+    assert_eq!(
+        kinds("/** Adds"),
+        [TokenKind::DocComment(
+            "/** Adds".into(),
+            IsTerminated::Unterminated
+        )]
+    );
+
+    // Extra delimiter characters make ordinary comments
+    assert_eq!(
+        kinds("//// Rule"),
+        [TokenKind::SinglelineComment("//// Rule".into())]
+    );
+    assert_eq!(
+        kinds("/*** Rule */"),
+        [TokenKind::MultilineComment(
+            "/*** Rule */".into(),
+            IsTerminated::Terminated
+        )]
+    );
+    assert_eq!(
+        kinds("/**/"),
+        [TokenKind::MultilineComment(
+            "/**/".into(),
+            IsTerminated::Terminated
+        )]
+    );
+}
+
+#[test]
+fn doc_comment_ends_at_line_end() {
+    assert_eq!(
+        kinds("/// Adds\nadd"),
+        [
+            TokenKind::DocComment("/// Adds".into(), IsTerminated::Terminated),
+            TokenKind::LineSpacing(LineSpacingAtom { count: 1 }),
+            identifier("add"),
+        ]
+    );
+}
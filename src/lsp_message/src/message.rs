@@ -1,7 +1,7 @@
 use crate::{LspNotification, LspRequest, LspResponse};
 use connection::Connection;
 use derive_more::From;
-use request::{BlockOn, CachedAft, PfIn};
+use request::{BlockOn, BuildOptions, CachedAft, PfIn};
 use serde::{Deserialize, Serialize};
 use std::io::{self, BufRead, Write};
 
@@ -14,6 +14,10 @@ pub enum LspMessage {
     ExtCompile(ExtCompile),
     ExtAft(ExtAft),
     ExtError(ExtError),
+    ExtStop(ExtStop),
+    ExtListInputs(ExtListInputs),
+    ExtInputs(ExtInputs),
+    ExtResolveDependencies(ExtResolveDependencies),
 }
 
 #[derive(Clone, Debug, From, Serialize, Deserialize)]
@@ -24,6 +28,8 @@ pub struct ExtAft {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ExtCompile {
     pub ext_compile: String,
+    #[serde(default)]
+    pub ext_options: BuildOptions,
 }
 
 #[derive(Clone, Debug, From, Serialize, Deserialize)]
@@ -31,6 +37,31 @@ pub struct ExtError {
     pub ext_error: String,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExtStop {
+    pub ext_stop: (),
+}
+
+/// Asks the daemon which paths on disk the last compilation of a file depended on
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExtListInputs {
+    pub ext_list_inputs: String,
+    #[serde(default)]
+    pub ext_options: BuildOptions,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExtInputs {
+    pub ext_inputs: Vec<String>,
+}
+
+/// Asks the daemon for the packages the project in a directory was built with,
+/// which is answered from the same cache as the last compilation
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExtResolveDependencies {
+    pub ext_resolve_dependencies: String,
+}
+
 #[derive(Serialize)]
 struct JsonRpc<'a> {
     jsonrpc: &'static str,
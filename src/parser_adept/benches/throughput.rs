@@ -0,0 +1,87 @@
+//! Measures lexer and parser throughput over a large generated source file.
+//!
+//! Run using `cargo bench -p parser_adept`, optionally passing the size of the
+//! generated source in megabytes, like `cargo bench -p parser_adept -- 16`.
+
+use document::Document;
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+use token::TokenKind;
+use util_infinite_iterator::InfiniteIterator;
+use util_text::{CharacterPeeker, Positioned};
+
+const DEFAULT_MEGABYTES: usize = 4;
+
+/// Each benchmark is repeated until at least this much time has passed,
+/// and the fastest run is reported
+const MIN_DURATION: Duration = Duration::from_secs(2);
+
+fn main() {
+    let megabytes = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_MEGABYTES);
+
+    let content = generate(megabytes * 1024 * 1024);
+
+    bench("lexer", &content, || {
+        let characters = Positioned::new(content.chars());
+        let mut lexer = lexer_adept::Lexer::new(CharacterPeeker::new(characters));
+        let mut count = 0;
+
+        while !matches!(lexer.next().kind, TokenKind::EndOfFile) {
+            count += 1;
+        }
+        count
+    });
+
+    let document = Document::new(&content);
+
+    bench("parser", &content, || {
+        parser_adept::reparse(&document, None, document.full_range())
+    });
+}
+
+fn bench<T>(name: &str, content: &str, mut f: impl FnMut() -> T) {
+    let started = Instant::now();
+    let mut fastest = Duration::MAX;
+    let mut runs = 0;
+
+    while runs == 0 || started.elapsed() < MIN_DURATION {
+        let start = Instant::now();
+        black_box(f());
+        fastest = fastest.min(start.elapsed());
+        runs += 1;
+    }
+
+    let megabytes = content.len() as f64 / (1024.0 * 1024.0);
+
+    println!(
+        "{:<8} {:>8.2} MB/s  ({:.2} MB in {:.2?}, fastest of {} runs)",
+        name,
+        megabytes / fastest.as_secs_f64(),
+        megabytes,
+        fastest,
+        runs
+    );
+}
+
+/// Generates Adept source code of roughly `len` bytes that covers most kinds of tokens
+fn generate(len: usize) -> String {
+    let mut content = String::with_capacity(len + 1024);
+    let mut i = 0;
+
+    while content.len() < len {
+        content.push_str(&format!(
+            "/* Generated binding {i} */\n\
+             value_{i} :: @fn(x: Nat, y: Nat): Nat {{ add(x, mul(y, {i})) }}\n\
+             record_{i} :: @record({i}, \"text {i}\\n\", 'c', 2.5e3, 0xFF) // trailing comment\n\
+             pick_{i} :: @if(true, value_{i}(1, 2), record_{i})\n\n"
+        ));
+        i += 1;
+    }
+
+    content
+}
@@ -1,53 +1,102 @@
 use document::{Document, DocumentRange};
 use lazy_format::lazy_format;
+use lexer_adept::{Checkpoint, LineCheckpoints, LineEdit};
+use parser_abstract::Parser as _;
 use std::{fmt::Display, sync::Arc};
-use syntax_tree::{BareSyntaxKind, BareSyntaxNode, BuiltinType, Reparsable, SyntaxNode};
-use text_edit::{LineIndex, TextLengthUtf16, TextPointUtf16};
-use token::{Directive, Punct, Token, TokenKind};
-use util_infinite_iterator::Peekable;
-use util_text::{Character, CharacterPeeker, LineSpacingAtom};
-
-pub struct Parser<II: Peekable<Token<()>>> {
+use syntax_tree::{
+    BareSyntaxKind, BareSyntaxNode, BuiltinType, FloatValue, Reparsable, SyntaxNode,
+};
+use text_edit::{LineIndex, SourcePosition, TextLengthUtf16, TextPointUtf16};
+use token::{Directive, Punct, StringLiteral, StringModifier, Token, TokenKind, doc_comment_text};
+use unicode_normalization::{IsNormalized, UnicodeNormalization, is_nfc_quick};
+use util_infinite_iterator::{Adapter, InfiniteIterator, Peekable, Peeker};
+use util_text::{CharacterPeeker, LineSpacingAtom, Positioned};
+
+mod unit_tests;
+
+pub struct Parser<'a, II: Peekable<Token<SourcePosition>>> {
     lexer: II,
+    content: &'a str,
 }
 
+const OPENING_BRACKETS: [&str; 3] = ["(", "[", "{"];
+const CLOSING_BRACKETS: [&str; 3] = [")", "]", "}"];
+const UNARY_OPERATORS: [&str; 3] = ["!", "~", "-"];
+
 pub enum ErrorRecovery {
     Empty,
     EatOne,
     EatUntilNestedClosing(TokenKind),
 }
 
-impl<II> Parser<II>
+impl<'a, II> Parser<'a, II>
 where
-    II: Peekable<Token<()>>,
+    II: Peekable<Token<SourcePosition>>,
 {
-    pub fn new(lexer: II) -> Self {
-        Self { lexer }
+    pub fn new(lexer: II, content: &'a str) -> Self {
+        Self { lexer, content }
+    }
+
+    /// The exact source text of a token
+    fn text(&self, token: &Token<SourcePosition>) -> String {
+        self.content[token.source.byte..token.end.byte].into()
     }
 
     pub fn run(&mut self) -> Arc<BareSyntaxNode> {
         let mut children = Vec::new();
-        self.parse_all_whitespace(&mut children);
+        self.parse_top_level_whitespace(&mut children);
 
         while !self.lexer.peek().is_end_of_file() {
-            children.push(self.parse_top_level());
-            self.parse_all_whitespace(&mut children);
+            self.parse_top_level(&mut children);
+            self.parse_top_level_whitespace(&mut children);
         }
 
         BareSyntaxNode::new_parent(BareSyntaxKind::Root, children)
     }
 
-    fn parse_top_level(&mut self) -> Arc<BareSyntaxNode> {
+    fn parse_top_level(&mut self, children: &mut Vec<Arc<BareSyntaxNode>>) {
         if self.should_parse_binding() {
-            return self.parse_binding();
+            self.parse_binding(children);
+            return;
         }
 
-        BareSyntaxNode::new_leaf(
-            BareSyntaxKind::Error {
-                description: "Expected top-level binding".into(),
-            },
-            self.lexer.next().kind.to_string(),
-        )
+        if let Some(directive) = self.lexer.eat(|token| match token.kind {
+            TokenKind::Directive(directive) if directive.as_ref() == "import" => Ok(directive),
+            _ => Err(token),
+        }) {
+            children.push(self.parse_import(directive));
+            return;
+        }
+
+        // A run of unexpected tokens is a single error, which ends where the next binding starts
+        let start = self.lexer.peek().source;
+        let mut end = self.lexer.next().end;
+
+        while !self.lexer.peek().is_end_of_file() && !self.is_top_level_start_ahead() {
+            end = self.lexer.next().end;
+        }
+
+        children.push(BareSyntaxNode::new_error(
+            self.content[start.byte..end.byte].into(),
+            "Expected top-level binding",
+        ));
+    }
+
+    /// Removes the doc comments that directly precede the next node, along with
+    /// the whitespace between them. A blank line detaches earlier doc comments.
+    fn take_doc_comments(children: &mut Vec<Arc<BareSyntaxNode>>) -> Vec<Arc<BareSyntaxNode>> {
+        let mut start = children.len();
+
+        for (i, child) in children.iter().enumerate().rev() {
+            match child.kind() {
+                BareSyntaxKind::DocComment(_) => start = i,
+                BareSyntaxKind::ColumnSpacing(_) => (),
+                BareSyntaxKind::LineSpacing(atom) if atom.count == 1 => (),
+                _ => break,
+            }
+        }
+
+        children.split_off(start)
     }
 
     fn should_parse_binding(&mut self) -> bool {
@@ -66,13 +115,62 @@ where
         true
     }
 
+    /// Whether the token at `index` starts a binding at the beginning of a line.
+    /// Error recovery never goes past one, so that an error can't spread to later bindings.
+    fn is_binding_start_at(&mut self, index: usize) -> bool {
+        let token = self.lexer.peek_nth(index);
+
+        if !token.kind.is_identifier() || token.source.point.col.0 != 0 {
+            return false;
+        }
+
+        let (after, _) = self
+            .lexer
+            .peek_skipping(index + 1, |token| token.kind.is_column_spacing());
+
+        after.kind.is_punct_of(Punct::new("::"))
+    }
+
+    /// Whether a binding starts at the next token, or at the start of the next line
+    fn is_binding_start_ahead(&mut self) -> bool {
+        self.is_binding_start_at(0)
+            || (self.lexer.peek().is_line_spacing() && self.is_binding_start_at(1))
+    }
+
+    /// Whether a binding or an import starts at the beginning of the next line
+    fn is_top_level_start_ahead(&mut self) -> bool {
+        if !self.lexer.peek().is_line_spacing() {
+            return false;
+        }
+
+        let next = self.lexer.peek_nth(1);
+        let is_import = matches!(
+            &next.kind,
+            TokenKind::Directive(directive) if directive.as_ref() == "import"
+        ) && next.source.point.col.0 == 0;
+
+        is_import || self.is_binding_start_at(1)
+    }
+
+    /// Whether a list should stop, which is also the case when its closing
+    /// bracket is missing and the list runs into an enclosing construct
+    fn is_list_end(&mut self) -> bool {
+        let token = self.lexer.peek();
+
+        token.is_end_of_file()
+            || CLOSING_BRACKETS
+                .iter()
+                .any(|close| token.is_punct_of(Punct::new(close)))
+            || self.is_binding_start_ahead()
+    }
+
     fn error_for_empty(description: impl Display) -> Arc<BareSyntaxNode> {
         BareSyntaxNode::new_error("".into(), description.to_string())
     }
 
     fn error_for_next_token(&mut self, description: impl Display) -> Arc<BareSyntaxNode> {
         let token = self.lexer.next();
-        BareSyntaxNode::new_error(token.kind.to_string(), description.to_string())
+        BareSyntaxNode::new_error(self.text(&token), description.to_string())
     }
 
     fn error_until(
@@ -88,22 +186,44 @@ where
         closing_tokens: &[TokenKind],
         description: impl Display,
     ) -> Arc<BareSyntaxNode> {
-        let mut raw_content = String::new();
+        let start = self.lexer.peek().source;
+        let mut end = start;
 
-        while self
-            .lexer
-            .eat(|token| {
-                if closing_tokens.contains(&token.kind) || token.is_end_of_file() {
-                    Err(token)
-                } else {
-                    raw_content.push_str(&token.to_string());
-                    Ok(())
+        // Closing brackets for the brackets opened within the erroneous tokens
+        let mut nesting = Vec::<Punct>::new();
+
+        loop {
+            if self.is_binding_start_ahead() {
+                break;
+            }
+
+            let token = self.lexer.peek();
+
+            if token.is_end_of_file()
+                || (nesting.is_empty() && closing_tokens.contains(&token.kind))
+            {
+                break;
+            }
+
+            if let TokenKind::Punct(punct) = token.kind {
+                if let Some(i) = OPENING_BRACKETS.iter().position(|open| punct.is(open)) {
+                    nesting.push(Punct::new(CLOSING_BRACKETS[i]));
+                } else if punct.is_any(&CLOSING_BRACKETS) {
+                    // Brackets that weren't opened here belong to an enclosing construct
+                    let Some(i) = nesting.iter().rposition(|close| *close == punct) else {
+                        break;
+                    };
+                    nesting.truncate(i);
                 }
-            })
-            .is_some()
-        {}
+            }
 
-        BareSyntaxNode::new_error(raw_content, description.to_string())
+            end = self.lexer.next().end;
+        }
+
+        BareSyntaxNode::new_error(
+            self.content[start.byte..end.byte].into(),
+            description.to_string(),
+        )
     }
 
     fn parse_name_required(&mut self) -> Arc<BareSyntaxNode> {
@@ -114,8 +234,10 @@ where
     fn parse_name(&mut self) -> Option<Arc<BareSyntaxNode>> {
         self.lexer.eat(|token| match token.kind {
             TokenKind::Identifier(name) => {
-                let identifier =
-                    BareSyntaxNode::new_leaf(BareSyntaxKind::Identifier(name.clone().into()), name);
+                let identifier = BareSyntaxNode::new_leaf(
+                    BareSyntaxKind::Identifier(normalize_identifier(&name)),
+                    name,
+                );
 
                 Ok(BareSyntaxNode::new_parent(
                     BareSyntaxKind::Name,
@@ -126,11 +248,14 @@ where
         })
     }
 
-    fn parse_binding(&mut self) -> Arc<BareSyntaxNode> {
-        let mut children = Vec::new();
+    /// Parses a binding into `siblings`, along with any doc comments after it
+    fn parse_binding(&mut self, siblings: &mut Vec<Arc<BareSyntaxNode>>) {
+        let mut children = Self::take_doc_comments(siblings);
         children.push(self.parse_name_required());
         self.parse_column_whitespace(&mut children);
 
+        let mut trailing = Vec::new();
+
         if self
             .parse_punct(Punct::new("::"), &mut children, ErrorRecovery::Empty)
             .is_ok()
@@ -138,20 +263,172 @@ where
             self.parse_column_whitespace(&mut children);
             children.push(self.parse_term());
 
-            if self.parse_all_whitespace(&mut children).is_none()
+            if self.parse_top_level_whitespace(&mut trailing).is_none()
                 && !self.lexer.peek().is_end_of_file()
+                && !self.is_binding_start_at(0)
             {
-                children.push(Self::error_for_empty("Expected newline after binding"))
+                trailing.push(Self::error_for_empty("Expected newline after binding"))
             }
         }
 
-        BareSyntaxNode::new_parent(BareSyntaxKind::Binding, children)
+        // Doc comments after the binding document whatever comes next instead
+        let next_doc = trailing
+            .iter()
+            .position(|child| matches!(child.kind(), BareSyntaxKind::DocComment(_)))
+            .unwrap_or(trailing.len());
+        let after = trailing.split_off(next_doc);
+
+        children.extend(trailing);
+        siblings.push(BareSyntaxNode::new_parent(
+            BareSyntaxKind::Binding,
+            children,
+        ));
+        siblings.extend(after);
+    }
+
+    fn parse_import(&mut self, directive: Directive) -> Arc<BareSyntaxNode> {
+        // @import "relative/path.adept"
+
+        let mut children = Vec::new();
+        children.push(BareSyntaxNode::new_leaf(
+            BareSyntaxKind::Directive(directive.clone()),
+            directive.to_string(),
+        ));
+        self.parse_column_whitespace(&mut children);
+
+        children.push(
+            self.lexer
+                .eat(|token| match token.kind {
+                    TokenKind::String(string) => Ok(Self::string_leaf(*string)),
+                    _ => Err(token),
+                })
+                .unwrap_or_else(|| Self::error_for_empty("Expected filename to import")),
+        );
+
+        if self.parse_top_level_whitespace(&mut children).is_none()
+            && !self.lexer.peek().is_end_of_file()
+        {
+            children.push(Self::error_for_empty("Expected newline after import"))
+        }
+
+        BareSyntaxNode::new_parent(BareSyntaxKind::Import, children)
+    }
+
+    fn string_leaf(string: StringLiteral) -> Arc<BareSyntaxNode> {
+        let text = || string.as_str().unwrap_or_default();
+
+        let kind = match string.modifier() {
+            StringModifier::Normal | StringModifier::RawString => {
+                BareSyntaxKind::String(text().into())
+            }
+            StringModifier::CString => BareSyntaxKind::CString(text().into()),
+            StringModifier::ByteString => {
+                BareSyntaxKind::ByteString(string.value.as_slice().into())
+            }
+            StringModifier::Character => {
+                BareSyntaxKind::Character(text().chars().next().unwrap_or_default())
+            }
+        };
+
+        if string.errors.is_empty() {
+            return BareSyntaxNode::new_leaf(kind, string.literal);
+        }
+
+        // Split up the literal so that each error only covers the problematic part
+        let literal = &string.literal;
+        let mut children = vec![];
+        let mut next = 0;
+
+        for error in string.errors.iter() {
+            if error.range.start > next {
+                children.push(BareSyntaxNode::new_leaf(
+                    BareSyntaxKind::StringFragment,
+                    literal[next..error.range.start].into(),
+                ));
+            }
+
+            children.push(BareSyntaxNode::new_error(
+                literal[error.range.clone()].into(),
+                error.kind.to_string(),
+            ));
+            next = error.range.end;
+        }
+
+        if next < literal.len() {
+            children.push(BareSyntaxNode::new_leaf(
+                BareSyntaxKind::StringFragment,
+                literal[next..].into(),
+            ));
+        }
+
+        BareSyntaxNode::new_parent(kind, children)
     }
 
     fn parse_term(&mut self) -> Arc<BareSyntaxNode> {
+        self.parse_term_with_precedence(0)
+    }
+
+    /// Parses infix operators using precedence climbing, where operators
+    /// with a precedence below `min_precedence` are left for the caller
+    fn parse_term_with_precedence(&mut self, min_precedence: usize) -> Arc<BareSyntaxNode> {
+        let mut children = self.parse_operand();
+
+        while let Some(precedence) = binary_precedence(self.lexer.peek())
+            && precedence >= min_precedence
+        {
+            let operator = self.lexer.next().kind.unwrap_punct();
+
+            let mut op_children = vec![
+                BareSyntaxNode::new_parent(BareSyntaxKind::Term, children),
+                BareSyntaxNode::new_punct(operator),
+            ];
+
+            // Long expressions can continue on the next line after an operator,
+            // but never into the next binding
+            self.parse_all_whitespace(&mut op_children);
+
+            if self.is_binding_start_ahead() {
+                op_children.push(BareSyntaxNode::new_parent(
+                    BareSyntaxKind::Term,
+                    vec![Self::error_for_empty(lazy_format!(
+                        "Expected expression after `{}`",
+                        operator
+                    ))],
+                ));
+            } else {
+                op_children.push(self.parse_term_with_precedence(precedence + 1));
+            }
+
+            children = vec![BareSyntaxNode::new_parent(
+                BareSyntaxKind::BinaryOp,
+                op_children,
+            )];
+        }
+
+        BareSyntaxNode::new_parent(BareSyntaxKind::Term, children)
+    }
+
+    /// Parses a term without infix operators, along with its surrounding column whitespace
+    fn parse_operand(&mut self) -> Vec<Arc<BareSyntaxNode>> {
         let mut top_children = vec![];
         self.parse_column_whitespace(&mut top_children);
 
+        if let Some(operator) = self.lexer.eat(|token| match token.kind {
+            TokenKind::Punct(punct) if punct.is_any(&UNARY_OPERATORS) => Ok(punct),
+            _ => Err(token),
+        }) {
+            let mut children = vec![BareSyntaxNode::new_punct(operator)];
+            children.push(BareSyntaxNode::new_parent(
+                BareSyntaxKind::Term,
+                self.parse_operand(),
+            ));
+            top_children.push(BareSyntaxNode::new_parent(
+                BareSyntaxKind::UnaryOp,
+                children,
+            ));
+            return top_children;
+        }
+
         let mut term_inner = vec![self.parse_term_inner()];
 
         let term_children = loop {
@@ -164,7 +441,7 @@ where
         };
 
         top_children.extend(term_children);
-        BareSyntaxNode::new_parent(BareSyntaxKind::Term, top_children)
+        top_children
     }
 
     fn parse_term_post(
@@ -238,6 +515,20 @@ where
             return Ok(BareSyntaxNode::new_parent(BareSyntaxKind::Nth, children));
         }
 
+        if self.lexer.peek().is_punct_of(Punct::new(".")) && self.lexer.peek_nth(1).is_identifier()
+        {
+            children = vec![
+                BareSyntaxNode::new_parent(BareSyntaxKind::Term, children),
+                BareSyntaxNode::new_punct(self.lexer.next().kind.unwrap_punct()),
+                self.parse_name_required(),
+            ];
+
+            return Ok(BareSyntaxNode::new_parent(
+                BareSyntaxKind::FieldAccess,
+                children,
+            ));
+        }
+
         Err(children)
     }
 
@@ -268,6 +559,14 @@ where
                 BareSyntaxKind::Integer(Arc::clone(value)),
                 text.into(),
             )),
+            TokenKind::String(string) => Ok(Self::string_leaf(*string.clone())),
+            TokenKind::Float(value, text) => Ok(BareSyntaxNode::new_leaf(
+                BareSyntaxKind::Float(FloatValue(*value)),
+                text.into(),
+            )),
+            TokenKind::MalformedNumber(text, error) => {
+                Ok(BareSyntaxNode::new_error(text.into(), error.to_string()))
+            }
             TokenKind::Identifier(name) => match name.as_str() {
                 "Type" => Ok(BareSyntaxNode::new_leaf(
                     BareSyntaxKind::BuiltinType(BuiltinType::Type),
@@ -298,12 +597,19 @@ where
                     name.into(),
                 )),
                 variable_name => Ok(BareSyntaxNode::new_leaf(
-                    BareSyntaxKind::Variable(variable_name.into()),
+                    BareSyntaxKind::Variable(normalize_identifier(variable_name)),
                     name.into(),
                 )),
             },
             _ => Err(token),
         }) {
+            if let BareSyntaxKind::Variable(namespace) = node.kind()
+                && self.lexer.peek().is_punct_of(Punct::new("::"))
+            {
+                let text = node.text().unwrap_or_default().to_string();
+                return self.parse_qualified_variable(namespace.clone(), text);
+            }
+
             return node;
         }
 
@@ -322,6 +628,41 @@ where
         }
     }
 
+    fn parse_qualified_variable(
+        &mut self,
+        namespace: Arc<str>,
+        namespace_text: String,
+    ) -> Arc<BareSyntaxNode> {
+        let mut children = vec![BareSyntaxNode::new_leaf(
+            BareSyntaxKind::Identifier(namespace.clone()),
+            namespace_text,
+        )];
+
+        let _ = self.parse_punct(Punct::new("::"), &mut children, ErrorRecovery::Empty);
+
+        let name = self.lexer.eat(|token| match token.kind {
+            TokenKind::Identifier(text) => Ok((normalize_identifier(&text), text)),
+            _ => Err(token),
+        });
+
+        if let Some((name, text)) = &name {
+            children.push(BareSyntaxNode::new_leaf(
+                BareSyntaxKind::Identifier(name.clone()),
+                text.clone(),
+            ));
+        } else {
+            children.push(Self::error_for_empty("Expected name after `::`"));
+        }
+
+        BareSyntaxNode::new_parent(
+            BareSyntaxKind::QualifiedVariable {
+                namespace,
+                name: name.map(|(name, _)| name),
+            },
+            children,
+        )
+    }
+
     fn parse_directive(&mut self, directive: Directive) -> Arc<BareSyntaxNode> {
         match &directive {
             Directive::Standard(name) => match name.as_ref() {
@@ -334,7 +675,17 @@ where
                 "Fn" => self.parse_fn_type_directive(directive),
                 "Record" => self.parse_record_type_directive(directive),
                 "record" => self.parse_record_directive(directive),
+                "Variant" => self.parse_variant_type_directive(directive),
+                "variant" => self.parse_variant_directive(directive),
                 "eval" => self.parse_eval(directive),
+                "import" => BareSyntaxNode::new_error(
+                    directive.to_string(),
+                    "Imports are only allowed at the top level",
+                ),
+                "so" => BareSyntaxNode::new_error(
+                    directive.to_string(),
+                    "Motives are only allowed after the value of a match",
+                ),
                 _ => BareSyntaxNode::new_error(
                     directive.to_string(),
                     format!("Directive `{}` is not supported yet", name),
@@ -378,6 +729,14 @@ where
                     children.push(
                         self.error_until(token_kind, lazy_format!("Expected `{}`", expected)),
                     );
+
+                    // When recovering until the expected token itself, it is kept
+                    if self.lexer.peek().is_punct_of(expected) {
+                        children.push(BareSyntaxNode::new_leaf(
+                            BareSyntaxKind::Punct(expected),
+                            self.lexer.next().kind.to_string(),
+                        ));
+                    }
                 }
             }
             Err(())
@@ -464,7 +823,6 @@ where
             let _ = self.parse_punct(
                 Punct::new("}"),
                 &mut children,
-                // TODO: This should take ideally nesting into account
                 ErrorRecovery::EatUntilNestedClosing(TokenKind::Punct(Punct::new("}"))),
             );
         }
@@ -478,6 +836,11 @@ where
             true => 0,
             false => 1,
         }
+
+        @match n {
+            0 => true
+            @nat_succ(m) => is_odd(m)
+        }
         */
 
         let mut children = Vec::new();
@@ -488,10 +851,46 @@ where
 
         self.parse_column_whitespace(&mut children);
         children.push(self.parse_term());
+
+        if matches!(
+            &self.lexer.peek().kind,
+            TokenKind::Directive(Directive::Standard(name)) if name.as_ref() == "so"
+        ) {
+            children.push(self.parse_match_motive());
+        }
+
+        self.parse_all_whitespace(&mut children);
         children.push(self.parse_match_block());
         BareSyntaxNode::new_parent(BareSyntaxKind::Match, children)
     }
 
+    fn parse_match_motive(&mut self) -> Arc<BareSyntaxNode> {
+        let so = self.lexer.next();
+        let text = self.text(&so);
+        let mut children = vec![BareSyntaxNode::new_leaf(
+            BareSyntaxKind::Directive(so.kind.unwrap_directive()),
+            text,
+        )];
+
+        self.parse_column_whitespace(&mut children);
+
+        // The binder is optional for motives that don't depend on the value
+        if self.lexer.peek().is_punct_of(Punct::new("(")) {
+            let _ = self.parse_punct(Punct::new("("), &mut children, ErrorRecovery::Empty);
+            self.parse_column_whitespace(&mut children);
+            children.push(self.parse_name_required());
+            self.parse_column_whitespace(&mut children);
+            let _ = self.parse_punct(
+                Punct::new(")"),
+                &mut children,
+                ErrorRecovery::EatUntilNestedClosing(TokenKind::Punct(Punct::new(")"))),
+            );
+        }
+
+        children.push(self.parse_term());
+        BareSyntaxNode::new_parent(BareSyntaxKind::MatchMotive, children)
+    }
+
     fn parse_match_block(&mut self) -> Arc<BareSyntaxNode> {
         let mut children = Vec::new();
         if self
@@ -499,13 +898,26 @@ where
             .is_ok()
         {
             self.parse_all_whitespace(&mut children);
-            children.push(self.parse_match_arm());
-            self.parse_all_whitespace(&mut children);
+
+            while !self.is_list_end() {
+                children.push(self.parse_match_arm());
+
+                let needs_separator = self.parse_all_whitespace(&mut children).is_none();
+
+                if self.is_list_end() {
+                    break;
+                } else if self.lexer.peek().is_punct_of(Punct::new(",")) {
+                    let _ = self.parse_punct(Punct::new(","), &mut children, ErrorRecovery::Empty);
+                    self.parse_all_whitespace(&mut children);
+                } else if needs_separator {
+                    children
+                        .push(self.error_for_next_token("Expected ',' or newline after match arm"));
+                }
+            }
 
             let _ = self.parse_punct(
                 Punct::new("}"),
                 &mut children,
-                // TODO: This should take ideally nesting into account
                 ErrorRecovery::EatUntilNestedClosing(TokenKind::Punct(Punct::new("}"))),
             );
         }
@@ -516,6 +928,7 @@ where
     fn parse_match_arm(&mut self) -> Arc<BareSyntaxNode> {
         let mut children = Vec::new();
         children.push(self.parse_pattern());
+        self.parse_column_whitespace(&mut children);
         let _ = self.parse_punct(Punct::new("=>"), &mut children, ErrorRecovery::Empty);
         children.push(self.parse_term());
 
@@ -525,6 +938,52 @@ where
     fn parse_pattern(&mut self) -> Arc<BareSyntaxNode> {
         let mut children = Vec::new();
 
+        if matches!(
+            &self.lexer.peek().kind,
+            TokenKind::Directive(Directive::Standard(name)) if name.as_ref() == "variant"
+        ) {
+            children.push(self.parse_variant_pattern());
+            return BareSyntaxNode::new_parent(BareSyntaxKind::Pattern, children);
+        }
+
+        if matches!(
+            &self.lexer.peek().kind,
+            TokenKind::Directive(Directive::Standard(name)) if name.as_ref() == "nat_succ"
+        ) {
+            let nat_succ = self.lexer.next();
+            let text = self.text(&nat_succ);
+            let mut nat_succ_children = vec![BareSyntaxNode::new_leaf(
+                BareSyntaxKind::Directive(nat_succ.kind.unwrap_directive()),
+                text,
+            )];
+
+            self.parse_column_whitespace(&mut nat_succ_children);
+
+            if self
+                .parse_punct(
+                    Punct::new("("),
+                    &mut nat_succ_children,
+                    ErrorRecovery::Empty,
+                )
+                .is_ok()
+            {
+                self.parse_column_whitespace(&mut nat_succ_children);
+                nat_succ_children.push(self.parse_pattern());
+                self.parse_column_whitespace(&mut nat_succ_children);
+                let _ = self.parse_punct(
+                    Punct::new(")"),
+                    &mut nat_succ_children,
+                    ErrorRecovery::EatUntilNestedClosing(TokenKind::Punct(Punct::new(")"))),
+                );
+            }
+
+            children.push(BareSyntaxNode::new_parent(
+                BareSyntaxKind::NatSucc,
+                nat_succ_children,
+            ));
+            return BareSyntaxNode::new_parent(BareSyntaxKind::Pattern, children);
+        }
+
         if let Some(node) = self.lexer.eat(|token| match &token.kind {
             TokenKind::Integer(value, text) => Ok(BareSyntaxNode::new_leaf(
                 BareSyntaxKind::Integer(Arc::clone(value)),
@@ -543,16 +1002,64 @@ where
                     BareSyntaxKind::VoidValue,
                     name.into(),
                 )),
+                "_" => Ok(BareSyntaxNode::new_leaf(
+                    BareSyntaxKind::Wildcard,
+                    name.into(),
+                )),
                 _ => Err(token),
             },
             _ => Err(token),
         }) {
             children.push(node);
+        } else if let Some(name) = self.parse_name() {
+            children.push(name);
+        } else {
+            children.push(Self::error_for_empty("Expected pattern"));
         }
 
         BareSyntaxNode::new_parent(BareSyntaxKind::Pattern, children)
     }
 
+    /// Parses `@variant Some(x)`, where each payload is matched by a nested pattern
+    fn parse_variant_pattern(&mut self) -> Arc<BareSyntaxNode> {
+        let variant = self.lexer.next();
+        let text = self.text(&variant);
+        let mut children = vec![BareSyntaxNode::new_leaf(
+            BareSyntaxKind::Directive(variant.kind.unwrap_directive()),
+            text,
+        )];
+
+        self.parse_column_whitespace(&mut children);
+        children.push(self.parse_name_required());
+
+        if self.lexer.peek().is_punct_of(Punct::new("(")) {
+            let _ = self.parse_punct(Punct::new("("), &mut children, ErrorRecovery::Empty);
+            self.parse_all_whitespace(&mut children);
+
+            let mut has_field = false;
+
+            while !self.is_list_end() {
+                if has_field {
+                    let _ = self.parse_punct(
+                        Punct::new(","),
+                        &mut children,
+                        ErrorRecovery::EatUntilNestedClosing(TokenKind::Punct(Punct::new(")"))),
+                    );
+                    self.parse_all_whitespace(&mut children);
+                } else {
+                    has_field = true;
+                }
+
+                children.push(self.parse_pattern());
+                self.parse_all_whitespace(&mut children);
+            }
+
+            let _ = self.parse_punct(Punct::new(")"), &mut children, ErrorRecovery::Empty);
+        }
+
+        BareSyntaxNode::new_parent(BareSyntaxKind::VariantValue, children)
+    }
+
     fn parse_intro_directive(
         &mut self,
         directive: Directive,
@@ -588,27 +1095,11 @@ where
         // Ternary with Motive (for dependent if)
         // @if(a >= b, @fn(_) { Nat }, a + b, a - b)
 
-        let mut children = Vec::new();
-        children.push(BareSyntaxNode::new_leaf(
-            BareSyntaxKind::Directive(directive.clone()),
-            directive.to_string(),
-        ));
-
-        self.parse_column_whitespace(&mut children);
-        let arg_list = self.parse_arg_list(Reparsable::Ignore);
-        children.push(arg_list);
-        self.parse_column_whitespace(&mut children);
-
-        BareSyntaxNode::new_parent(BareSyntaxKind::IfValue, children)
-
-        /*
-        // Ternary (for short single-line)
-        // @if(a >= b, a + b, a - b)
-
         // Traditional (for multi-line)
         // @if a >= b { a + b } else { a - b }
         // @if (a >= b) { a + b } else { a - b }
         // @if(a >= b){ a + b }else{ a - b }
+        // @if a >= b { a + b } else @if a > 0 { a } else { b }
 
         // With Motive (for dependent if)
         // @if(a >= b, a + b, a - b): Nat
@@ -623,59 +1114,142 @@ where
         self.parse_column_whitespace(&mut children);
 
         let has_block_args = if self.lexer.peek().is_punct_of(Punct::new("(")) {
-            let arg_list = self.parse_arg_list(Reparsable::Ignore);
+            self.parse_if_arg_list(&mut children)
+        } else {
+            children.push(BareSyntaxNode::new_parent(
+                BareSyntaxKind::IfCondition,
+                vec![self.parse_term()],
+            ));
+            true
+        };
 
-            let has_arg_comma = arg_list.children().any(|child| {
+        if has_block_args {
+            self.parse_all_whitespace(&mut children);
+            children.push(BareSyntaxNode::new_parent(
+                BareSyntaxKind::IfThen,
+                vec![self.parse_block()],
+            ));
+
+            let (after, _) = self.lexer.peek_skipping(0, |token| {
                 matches!(
-                    child.as_ref().kind(),
-                    BareSyntaxKind::Punct(punct) if *punct == Punct::new(","),
+                    token.kind,
+                    TokenKind::ColumnSpacing(_)
+                        | TokenKind::LineSpacing(_)
+                        | TokenKind::SinglelineComment(_)
+                        | TokenKind::MultilineComment(..)
                 )
             });
 
-            let has_an_arg = arg_list
-                .children()
-                .any(|child| matches!(child.as_ref().kind(), BareSyntaxKind::Term));
+            // Newlines are only part of the `@if` when it continues with `else`
+            if matches!(&after.kind, TokenKind::Identifier(name) if name == "else") {
+                self.parse_all_whitespace(&mut children);
 
-            children.push(arg_list);
-            !has_arg_comma && has_an_arg
-        } else {
-            children.push(self.parse_term());
-            true
-        };
+                let else_keyword = self.lexer.next();
+                children.push(BareSyntaxNode::new_leaf(
+                    BareSyntaxKind::Identifier("else".into()),
+                    self.text(&else_keyword),
+                ));
 
-        if has_block_args {
-            self.parse_all_whitespace(&mut children);
-            children.push(self.parse_block());
-            self.parse_all_whitespace(&mut children);
+                self.parse_all_whitespace(&mut children);
 
-            children.push(
-                if let Some(else_keyword) = self.lexer.eat(|token| match token.kind {
-                    TokenKind::Identifier(ident) if ident == "else" => {
-                        Ok(BareSyntaxNode::new_leaf(
-                            BareSyntaxKind::Identifier(ident.clone().into()),
-                            ident,
-                        ))
-                    }
-                    _ => Err(token),
-                }) {
-                    else_keyword
+                let otherwise = if matches!(
+                    &self.lexer.peek().kind,
+                    TokenKind::Directive(Directive::Standard(name)) if name.as_ref() == "if"
+                ) {
+                    self.parse_term()
                 } else {
-                    Self::error_for_empty("Expected `else` after first block of if")
-                },
-            );
+                    self.parse_block()
+                };
 
-            self.parse_all_whitespace(&mut children);
-            children.push(self.parse_block());
+                children.push(BareSyntaxNode::new_parent(
+                    BareSyntaxKind::IfElse,
+                    vec![otherwise],
+                ));
+            } else {
+                self.parse_column_whitespace(&mut children);
+                children.push(Self::error_for_empty(
+                    "Expected `else` after first block of if",
+                ));
+            }
         }
 
         self.parse_column_whitespace(&mut children);
-        self.parse_type_annotation(false, &mut children);
+
+        let mut motive = vec![];
+        self.parse_type_annotation(false, &mut motive);
+
+        let has_motive = children
+            .iter()
+            .any(|child| matches!(child.kind(), BareSyntaxKind::IfMotive));
+
+        if !motive.is_empty() && has_motive {
+            children.push(BareSyntaxNode::new_error(
+                motive.iter().map(|node| node.flatten()).collect::<String>(),
+                "Motive of if was already given as an argument",
+            ));
+        } else if !motive.is_empty() {
+            children.push(BareSyntaxNode::new_parent(BareSyntaxKind::IfMotive, motive));
+        }
+
         BareSyntaxNode::new_parent(BareSyntaxKind::IfValue, children)
-        */
+    }
+
+    /// Parses the parenthesized arguments of an `@if`, labelling each argument by its role.
+    /// Returns whether the arguments are only the condition, so blocks should follow.
+    fn parse_if_arg_list(&mut self, children: &mut Vec<Arc<BareSyntaxNode>>) -> bool {
+        let arg_list = self.parse_arg_list(Reparsable::Ignore);
+
+        let has_arg_comma = arg_list.children().any(|child| {
+            matches!(
+                child.as_ref().kind(),
+                BareSyntaxKind::Punct(punct) if *punct == Punct::new(","),
+            )
+        });
+
+        let arg_count = arg_list
+            .children()
+            .filter(|child| child.as_ref().kind().is_term())
+            .count();
+
+        let roles = match (arg_count, has_arg_comma) {
+            (1, false) => [BareSyntaxKind::IfCondition].as_slice(),
+            (3, _) => &[
+                BareSyntaxKind::IfCondition,
+                BareSyntaxKind::IfThen,
+                BareSyntaxKind::IfElse,
+            ],
+            (4, _) => &[
+                BareSyntaxKind::IfCondition,
+                BareSyntaxKind::IfMotive,
+                BareSyntaxKind::IfThen,
+                BareSyntaxKind::IfElse,
+            ],
+            _ => {
+                children.push(arg_list);
+                children.push(Self::error_for_empty(
+                    "Expected condition, optional motive, and two branches for if",
+                ));
+                return false;
+            }
+        };
+
+        let mut roles = roles.iter();
+
+        children.extend(arg_list.children().map(|child| {
+            if child.as_ref().kind().is_term() {
+                let role = roles.next().expect("role for each argument of if");
+                BareSyntaxNode::new_parent(role.clone(), vec![child.clone()])
+            } else {
+                child.clone()
+            }
+        }));
+
+        arg_count == 1 && !has_arg_comma
     }
 
     fn parse_record_directive(&mut self, directive: Directive) -> Arc<BareSyntaxNode> {
         // @record(12, true, Type, void)
+        // @record(x: 1, y: 2)
 
         let mut children = Vec::new();
         children.push(BareSyntaxNode::new_leaf(
@@ -684,12 +1258,38 @@ where
         ));
 
         self.parse_column_whitespace(&mut children);
-        children.push(self.parse_arg_list(Reparsable::Reparse));
+        children.push(self.parse_arg_list_with(Reparsable::Reparse, Self::parse_record_arg));
 
         BareSyntaxNode::new_parent(BareSyntaxKind::RecordValue, children)
     }
 
+    /// Parses a field of a record value, which is either positional or named like `x: 1`
+    fn parse_record_arg(&mut self) -> Arc<BareSyntaxNode> {
+        let (after, _) = self
+            .lexer
+            .peek_skipping(1, |token| token.kind.is_column_spacing());
+
+        if !after.kind.is_punct_of(Punct::new(":")) || !self.lexer.peek().kind.is_identifier() {
+            return self.parse_term();
+        }
+
+        let mut children = vec![self.parse_name_required()];
+        self.parse_column_whitespace(&mut children);
+        let _ = self.parse_punct(Punct::new(":"), &mut children, ErrorRecovery::Empty);
+        children.push(self.parse_term());
+
+        BareSyntaxNode::new_parent(BareSyntaxKind::FieldInit, children)
+    }
+
     fn parse_arg_list(&mut self, reparsable: Reparsable) -> Arc<BareSyntaxNode> {
+        self.parse_arg_list_with(reparsable, Self::parse_term)
+    }
+
+    fn parse_arg_list_with(
+        &mut self,
+        reparsable: Reparsable,
+        mut parse_arg: impl FnMut(&mut Self) -> Arc<BareSyntaxNode>,
+    ) -> Arc<BareSyntaxNode> {
         let mut children = vec![];
         if self
             .parse_punct(Punct::new("("), &mut children, ErrorRecovery::Empty)
@@ -699,7 +1299,7 @@ where
 
             let mut has_param = false;
 
-            while !self.lexer.peek().kind.is_punct_of_or_eof(Punct::new(")")) {
+            while !self.is_list_end() {
                 if has_param {
                     let _ = self.parse_punct(
                         Punct::new(","),
@@ -711,7 +1311,7 @@ where
                     has_param = true;
                 }
 
-                children.push(self.parse_term());
+                children.push(parse_arg(self));
                 self.parse_all_whitespace(&mut children);
             }
 
@@ -720,6 +1320,81 @@ where
         BareSyntaxNode::new_parent(BareSyntaxKind::ArgList(reparsable), children)
     }
 
+    fn parse_variant_type_directive(&mut self, directive: Directive) -> Arc<BareSyntaxNode> {
+        // @Variant { Some(T), None }
+
+        let mut children = Vec::new();
+        children.push(BareSyntaxNode::new_leaf(
+            BareSyntaxKind::Directive(directive.clone()),
+            directive.to_string(),
+        ));
+        self.parse_column_whitespace(&mut children);
+        children.push(self.parse_variant_def_list());
+        BareSyntaxNode::new_parent(BareSyntaxKind::BuiltinType(BuiltinType::Variant), children)
+    }
+
+    fn parse_variant_directive(&mut self, directive: Directive) -> Arc<BareSyntaxNode> {
+        // @variant Some(x)
+        // @variant None
+
+        let mut children = Vec::new();
+        children.push(BareSyntaxNode::new_leaf(
+            BareSyntaxKind::Directive(directive.clone()),
+            directive.to_string(),
+        ));
+        self.parse_column_whitespace(&mut children);
+        children.push(self.parse_name_required());
+
+        if self.lexer.peek().is_punct_of(Punct::new("(")) {
+            children.push(self.parse_arg_list(Reparsable::Reparse));
+        }
+
+        self.parse_column_whitespace(&mut children);
+        BareSyntaxNode::new_parent(BareSyntaxKind::VariantValue, children)
+    }
+
+    fn parse_variant_def_list(&mut self) -> Arc<BareSyntaxNode> {
+        let mut children = vec![];
+
+        if self
+            .parse_punct(Punct::new("{"), &mut children, ErrorRecovery::Empty)
+            .is_ok()
+        {
+            self.parse_all_whitespace(&mut children);
+
+            while !self.is_list_end() {
+                children.push(self.parse_variant_def());
+
+                let needs_separator = self.parse_all_whitespace(&mut children).is_none();
+
+                if self.is_list_end() {
+                    break;
+                } else if self.lexer.peek().is_punct_of(Punct::new(",")) {
+                    let _ = self.parse_punct(Punct::new(","), &mut children, ErrorRecovery::Empty);
+                    self.parse_all_whitespace(&mut children);
+                } else if needs_separator {
+                    children
+                        .push(self.error_for_next_token("Expected ',' or newline after variant"));
+                }
+            }
+
+            let _ = self.parse_punct(Punct::new("}"), &mut children, ErrorRecovery::Empty);
+        }
+        BareSyntaxNode::new_parent(BareSyntaxKind::VariantDefList, children)
+    }
+
+    /// Parses a variant of a sum type, whose payload types are given like arguments
+    fn parse_variant_def(&mut self) -> Arc<BareSyntaxNode> {
+        let mut children = vec![self.parse_name_required()];
+
+        if self.lexer.peek().is_punct_of(Punct::new("(")) {
+            children.push(self.parse_arg_list(Reparsable::Ignore));
+        }
+
+        self.parse_column_whitespace(&mut children);
+        BareSyntaxNode::new_parent(BareSyntaxKind::VariantDef, children)
+    }
+
     fn parse_field_def_list(&mut self) -> Arc<BareSyntaxNode> {
         let mut children = vec![];
 
@@ -729,12 +1404,12 @@ where
         {
             self.parse_all_whitespace(&mut children);
 
-            while !self.lexer.peek().kind.is_punct_of_or_eof(Punct::new("}")) {
+            while !self.is_list_end() {
                 children.push(self.parse_field_def_sublist());
 
                 let needs_separator = self.parse_all_whitespace(&mut children).is_none();
 
-                if self.lexer.peek().kind.is_punct_of_or_eof(Punct::new("}")) {
+                if self.is_list_end() {
                     break;
                 } else if self.lexer.peek().is_punct_of(Punct::new(",")) {
                     let _ = self.parse_punct(
@@ -773,7 +1448,7 @@ where
 
             let mut has_param = false;
 
-            while !self.lexer.peek().kind.is_punct_of_or_eof(Punct::new(")")) {
+            while !self.is_list_end() {
                 if has_param {
                     let _ = self.parse_punct(
                         Punct::new(","),
@@ -852,6 +1527,23 @@ where
 
     fn parse_implicit_name(&mut self) -> Option<Arc<BareSyntaxNode>> {
         let mut children = vec![];
+
+        // `$T` without spacing is lexed as a single token
+        if self.lexer.peek().kind.is_polymorph() {
+            let name = self.lexer.next().kind.unwrap_polymorph();
+
+            return Some(BareSyntaxNode::new_parent(
+                BareSyntaxKind::ImplicitName,
+                vec![
+                    BareSyntaxNode::new_punct(Punct::new("$")),
+                    BareSyntaxNode::new_leaf(
+                        BareSyntaxKind::Identifier(normalize_identifier(&name)),
+                        name,
+                    ),
+                ],
+            ));
+        }
+
         if self
             .parse_punct(Punct::new("$"), &mut children, ErrorRecovery::Empty)
             .is_ok()
@@ -861,7 +1553,7 @@ where
             if self.lexer.peek().is_identifier() {
                 let identifier = self.lexer.next().kind.unwrap_identifier();
                 children.push(BareSyntaxNode::new_leaf(
-                    BareSyntaxKind::Identifier(identifier.clone().into()),
+                    BareSyntaxKind::Identifier(normalize_identifier(&identifier)),
                     identifier,
                 ));
             } else {
@@ -899,87 +1591,217 @@ where
     fn parse_whitespace(
         &mut self,
         allow_newlines: bool,
+        stop_before_binding: bool,
         children: &mut Vec<Arc<BareSyntaxNode>>,
     ) -> Option<LineSpacingAtom> {
         let mut has_newline = None;
-
-        while self
-            .lexer
-            .eat(|token| match token.kind {
-                TokenKind::ColumnSpacing(atom) => {
-                    children.push(BareSyntaxNode::new_leaf(
-                        BareSyntaxKind::ColumnSpacing(atom),
-                        atom.to_string(),
-                    ));
-                    Ok(())
-                }
-                TokenKind::LineSpacing(atom) if allow_newlines => {
-                    has_newline = Some(atom);
-                    children.push(BareSyntaxNode::new_leaf(
-                        BareSyntaxKind::LineSpacing(atom),
-                        atom.to_string(),
-                    ));
-                    Ok(())
-                }
-                TokenKind::SinglelineComment(comment) => {
-                    children.push(BareSyntaxNode::new_leaf(
-                        BareSyntaxKind::SinglelineComment(comment.clone().into()),
-                        comment,
-                    ));
-                    Ok(())
-                }
-                TokenKind::MultilineComment(comment, terminated) => {
-                    children.push(BareSyntaxNode::new_leaf(
-                        BareSyntaxKind::MultilineComment(comment.clone().into()),
-                        comment,
-                    ));
-
-                    if terminated.is_unterminated() {
-                        children.push(Self::error_for_empty(
-                            "Expected `*/` to close multi-line comment",
+        let content = self.content;
+
+        while !(stop_before_binding
+            && self.lexer.peek().is_line_spacing()
+            && self.is_binding_start_at(1))
+            && self
+                .lexer
+                .eat(|token| match token.kind {
+                    TokenKind::ColumnSpacing(atom) => {
+                        children.push(BareSyntaxNode::new_leaf(
+                            BareSyntaxKind::ColumnSpacing(atom),
+                            content[token.source.byte..token.end.byte].into(),
+                        ));
+                        Ok(())
+                    }
+                    TokenKind::LineSpacing(atom) if allow_newlines => {
+                        has_newline = Some(atom);
+                        children.push(BareSyntaxNode::new_leaf(
+                            BareSyntaxKind::LineSpacing(atom),
+                            content[token.source.byte..token.end.byte].into(),
                         ));
+                        Ok(())
                     }
+                    TokenKind::SinglelineComment(comment) => {
+                        children.push(BareSyntaxNode::new_leaf(
+                            BareSyntaxKind::SinglelineComment(comment.clone().into()),
+                            comment,
+                        ));
+                        Ok(())
+                    }
+                    TokenKind::MultilineComment(comment, terminated) => {
+                        children.push(BareSyntaxNode::new_leaf(
+                            BareSyntaxKind::MultilineComment(comment.clone().into()),
+                            comment,
+                        ));
 
-                    Ok(())
-                }
-                _ => Err(token),
-            })
-            .is_some()
+                        if terminated.is_unterminated() {
+                            children.push(Self::error_for_empty(
+                                "Expected `*/` to close multi-line comment",
+                            ));
+                        }
+
+                        Ok(())
+                    }
+                    TokenKind::DocComment(comment, terminated) => {
+                        children.push(BareSyntaxNode::new_leaf(
+                            BareSyntaxKind::DocComment(doc_comment_text(&comment).into()),
+                            comment,
+                        ));
+
+                        if terminated.is_unterminated() {
+                            children
+                                .push(Self::error_for_empty("Expected `*/` to close doc comment"));
+                        }
+
+                        Ok(())
+                    }
+                    _ => Err(token),
+                })
+                .is_some()
         {}
 
         has_newline
     }
 
     fn parse_column_whitespace(&mut self, children: &mut Vec<Arc<BareSyntaxNode>>) {
-        self.parse_whitespace(false, children);
+        self.parse_whitespace(false, false, children);
     }
 
+    /// Parses whitespace including newlines, except for a newline that
+    /// ends right before a binding, so that a construct continuing onto
+    /// the next line can never take over the next binding
     fn parse_all_whitespace(
         &mut self,
         children: &mut Vec<Arc<BareSyntaxNode>>,
     ) -> Option<LineSpacingAtom> {
-        self.parse_whitespace(true, children)
+        self.parse_whitespace(true, true, children)
+    }
+
+    fn parse_top_level_whitespace(
+        &mut self,
+        children: &mut Vec<Arc<BareSyntaxNode>>,
+    ) -> Option<LineSpacingAtom> {
+        self.parse_whitespace(true, false, children)
     }
 }
 
+/// The precedence of the token as an infix operator, from `||` up to `*`
+fn binary_precedence(token: &Token<SourcePosition>) -> Option<usize> {
+    let precedence = token.kind.precedence();
+    (token.kind.is_punct() && (3..=12).contains(&precedence)).then_some(precedence)
+}
+
+/// Identifiers are compared in NFC, so that a name written using different but
+/// canonically equivalent characters is still the same name
+fn normalize_identifier(name: &str) -> Arc<str> {
+    match is_nfc_quick(name.chars()) {
+        IsNormalized::Yes => name.into(),
+        IsNormalized::No | IsNormalized::Maybe => name.nfc().collect::<String>().into(),
+    }
+}
+
+/// The tokens of a document along with where lexing can resume on each line,
+/// kept between reparses so that an edit only relexes the lines around it
+#[derive(Clone, Debug, Default)]
+pub struct Lexed {
+    checkpoints: LineCheckpoints,
+    tokens: Vec<Token<SourcePosition>>,
+    line_count: usize,
+}
+
+/// Parses a whole document, where `starting_state` is the lexer checkpoint to
+/// continue lexing the content from once the given tokens run out
+pub struct DocumentParser<'a> {
+    content: &'a str,
+}
+
+impl<'a> parser_abstract::Parser for DocumentParser<'a> {
+    type State = Checkpoint;
+    type Token = Token<SourcePosition>;
+    type Output = Arc<BareSyntaxNode>;
+
+    fn parse(
+        &self,
+        starting_state: Option<Self::State>,
+        tokens: impl InfiniteIterator<Item = Self::Token>,
+    ) -> Self::Output {
+        let lexer = starting_state.map(|checkpoint| {
+            let characters = Positioned::resume(
+                self.content[checkpoint.position.byte..].chars(),
+                checkpoint.position,
+            );
+            lexer_adept::Lexer::resume(CharacterPeeker::new(characters), checkpoint.state)
+        });
+
+        Parser::new(Peeker::new(ThenLex { tokens, lexer }), self.content).run()
+    }
+}
+
+/// Gives the known tokens, and then the tokens of the lexer once those run out
+struct ThenLex<II, L> {
+    tokens: II,
+    lexer: Option<L>,
+}
+
+impl<II, L> InfiniteIterator for ThenLex<II, L>
+where
+    II: InfiniteIterator<Item = Token<SourcePosition>>,
+    L: InfiniteIterator<Item = Token<SourcePosition>>,
+{
+    type Item = Token<SourcePosition>;
+
+    fn next(&mut self) -> Self::Item {
+        let token = self.tokens.next();
+
+        match &mut self.lexer {
+            Some(lexer) if token.is_end_of_file() => lexer.next(),
+            _ => token,
+        }
+    }
+}
+
+/// Parses `document` after the content in `range` was changed. When the tokens
+/// of the previous content are given, lexing resumes from the checkpoint of the
+/// first edited line and stops once it catches up with the previous tokens.
 pub fn reparse(
     document: &Document,
-    _existing: Option<Arc<SyntaxNode>>,
-    _range: DocumentRange,
+    previous: Option<&mut Lexed>,
+    range: DocumentRange,
 ) -> Arc<SyntaxNode> {
-    let adapter = util_infinite_iterator::Adapter::new(
-        document.chars().map(|c| Character::At(c, ())),
-        Character::End(()),
-    );
+    let content = document.chars().collect::<String>();
+    let parser = DocumentParser { content: &content };
+
+    let bare = match previous {
+        Some(lexed) => {
+            let line_count = document.line_count();
+            let edit = LineEdit {
+                start: range.start.line.0,
+                old_end: (range.end.line.0 + lexed.line_count).saturating_sub(line_count),
+                new_end: range.end.line.0,
+            };
 
-    let lexer =
-        util_infinite_iterator::Peeker::new(lexer_adept::Lexer::new(CharacterPeeker::new(adapter)));
+            let relexed = lexed.checkpoints.relex(&lexed.tokens, &content, edit);
+            *lexed = Lexed {
+                checkpoints: relexed.checkpoints,
+                tokens: relexed.tokens,
+                line_count,
+            };
 
-    let mut parser = Parser::new(lexer);
+            let end_of_file = lexed.tokens.last().cloned().expect("end of file token");
+            parser.parse(
+                None,
+                Adapter::new(lexed.tokens.iter().cloned(), end_of_file),
+            )
+        }
+        None => parser.parse(
+            Some(Checkpoint::START),
+            Adapter::new(
+                std::iter::empty(),
+                Token::new(TokenKind::EndOfFile, SourcePosition::start()),
+            ),
+        ),
+    };
 
     SyntaxNode::new(
         None,
-        parser.run(),
+        bare,
         TextPointUtf16 {
             line: LineIndex(0),
             col: TextLengthUtf16(0),
@@ -992,12 +1814,8 @@ fn test1() {
     let document = Document::new(r#""#.into());
 
     /*
-    let adapter = util_infinite_iterator::Adapter::new(
-        document.chars().map(|c| Character::At(c, ())),
-        Character::End(()),
-    );
-
-    let mut lexer = lexer_adept::Lexer::new(CharacterPeeker::new(adapter));
+    let characters = Positioned::new(document.chars());
+    let mut lexer = lexer_adept::Lexer::new(CharacterPeeker::new(characters));
 
     for item in lexer.as_iter(true) {
         println!("has {:?}", item);
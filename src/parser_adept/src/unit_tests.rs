@@ -0,0 +1,584 @@
+#![cfg(test)]
+
+use crate::{Lexed, reparse};
+use document::Document;
+use std::sync::Arc;
+use syntax_tree::{
+    BareSyntaxKind, SyntaxNode,
+    ast::{self, AstNode},
+};
+use text_edit::{TextEditUtf16, TextPointDiffUtf16, TextPointRangeUtf16, TextPointUtf16};
+
+fn parse(source: &str) -> Arc<SyntaxNode> {
+    let document = Document::new(source);
+    let syntax_tree = reparse(&document, None, document.full_range());
+    assert_eq!(
+        syntax_tree.bare().flatten(),
+        source,
+        "parsing must be lossless"
+    );
+    syntax_tree
+}
+
+/// The text and description of each error, sorted by position
+fn errors(syntax_tree: &Arc<SyntaxNode>) -> Vec<(String, String)> {
+    let mut errors = syntax_tree
+        .descendants()
+        .filter_map(|node| match node.bare().kind() {
+            BareSyntaxKind::Error { description } => Some((
+                node.text_range().start(),
+                node.bare().flatten(),
+                description.clone(),
+            )),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    errors.sort();
+    errors
+        .into_iter()
+        .map(|(_, text, description)| (text, description))
+        .collect()
+}
+
+fn binding_names(syntax_tree: &Arc<SyntaxNode>) -> Vec<String> {
+    ast::Root::cast(syntax_tree.clone())
+        .unwrap()
+        .bindings()
+        .flat_map(|binding| binding.name().and_then(|name| name.text()))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+#[test]
+fn unclosed_arg_list_stops_at_next_binding() {
+    let syntax_tree = parse("a :: f(1, \nb :: 3\n");
+
+    assert_eq!(binding_names(&syntax_tree), ["a", "b"]);
+    assert_eq!(
+        errors(&syntax_tree),
+        [
+            ("".into(), "Expected `)`".into()),
+            ("".into(), "Expected expression".into()),
+        ]
+    );
+}
+
+#[test]
+fn unclosed_nested_lists_stop_at_next_binding() {
+    let syntax_tree = parse("a :: @fn(x: Nat) { f(@record(x, \n\nb :: 3\n");
+
+    assert_eq!(binding_names(&syntax_tree), ["a", "b"]);
+    assert!(
+        errors(&syntax_tree)
+            .iter()
+            .all(|(text, _)| !text.contains("b ::"))
+    );
+}
+
+#[test]
+fn junk_at_top_level_is_a_single_error() {
+    let syntax_tree = parse("a :: 1\n) ) x y\nz\nc :: 4\n");
+
+    assert_eq!(binding_names(&syntax_tree), ["a", "c"]);
+    assert_eq!(
+        errors(&syntax_tree),
+        [(") ) x y\nz".into(), "Expected top-level binding".into())]
+    );
+}
+
+#[test]
+fn junk_at_top_level_stops_at_import() {
+    let syntax_tree = parse("x y\n@import \"other.adept\"\n");
+
+    assert_eq!(
+        errors(&syntax_tree),
+        [("x y".into(), "Expected top-level binding".into())]
+    );
+    assert_eq!(ast::Root::cast(syntax_tree).unwrap().imports().count(), 1);
+}
+
+/// Renders the value of the only binding in `source` with its operators fully parenthesized
+fn grouping(source: &str) -> String {
+    fn render(term: Option<ast::Term>) -> String {
+        let Some(term) = term else {
+            return "?".into();
+        };
+
+        match term.expr() {
+            Some(ast::Expr::BinaryOp(op)) => format!(
+                "({} {} {})",
+                render(op.lhs()),
+                op.operator()
+                    .map(|operator| operator.to_string())
+                    .unwrap_or_default(),
+                render(op.rhs())
+            ),
+            Some(ast::Expr::UnaryOp(op)) => format!(
+                "({}{})",
+                op.operator()
+                    .map(|operator| operator.to_string())
+                    .unwrap_or_default(),
+                render(op.operand())
+            ),
+            _ => term.syntax().bare().flatten().trim().to_string(),
+        }
+    }
+
+    let syntax_tree = parse(&format!("x :: {}\n", source));
+    assert_eq!(errors(&syntax_tree), []);
+
+    let root = ast::Root::cast(syntax_tree).unwrap();
+    render(root.bindings().next().and_then(|binding| binding.value()))
+}
+
+#[test]
+fn binary_operators_bind_by_precedence() {
+    assert_eq!(grouping("1 + 2 * 3"), "(1 + (2 * 3))");
+    assert_eq!(grouping("1 * 2 + 3"), "((1 * 2) + 3)");
+    assert_eq!(grouping("a + b < c * d"), "((a + b) < (c * d))");
+    assert_eq!(grouping("a || b && c == d"), "(a || (b && (c == d)))");
+    assert_eq!(grouping("(1 + 2) * 3"), "((1 + 2) * 3)");
+}
+
+#[test]
+fn unary_operators_bind_tighter_than_binary() {
+    assert_eq!(grouping("-a * b"), "((-a) * b)");
+    assert_eq!(grouping("!a && !b"), "((!a) && (!b))");
+}
+
+#[test]
+fn binary_operators_are_left_associative() {
+    assert_eq!(grouping("1 - 2 - 3"), "((1 - 2) - 3)");
+    assert_eq!(grouping("a / b / c"), "((a / b) / c)");
+    assert_eq!(grouping("a || b || c"), "((a || b) || c)");
+}
+
+#[test]
+fn operator_continues_on_next_line() {
+    assert_eq!(grouping("1 +\n    2"), "(1 + 2)");
+}
+
+#[test]
+fn missing_operand_stops_at_next_binding() {
+    let syntax_tree = parse("a :: 1 +\nb :: 3\n");
+
+    assert_eq!(binding_names(&syntax_tree), ["a", "b"]);
+    assert_eq!(
+        errors(&syntax_tree),
+        [("".into(), "Expected expression after `+`".into())]
+    );
+}
+
+#[test]
+fn if_motive_is_given_once() {
+    let syntax_tree = parse("x :: @if(true, Nat, 1, 2): Nat\n");
+    let motives = syntax_tree
+        .descendants()
+        .filter(|node| matches!(node.bare().kind(), BareSyntaxKind::IfMotive))
+        .count();
+
+    assert_eq!(motives, 1);
+    assert_eq!(
+        errors(&syntax_tree),
+        [(
+            ": Nat".into(),
+            "Motive of if was already given as an argument".into()
+        )]
+    );
+}
+
+#[test]
+fn if_motive_after_branches() {
+    let syntax_tree = parse("x :: @if(true, 1, 2): Nat\ny :: @if true { 1 } else { 2 }: Nat\n");
+
+    assert_eq!(errors(&syntax_tree), []);
+}
+
+/// The value of the only binding in `source`
+fn value(source: &str) -> ast::Expr {
+    let syntax_tree = parse(source);
+    assert_eq!(errors(&syntax_tree), []);
+
+    ast::Root::cast(syntax_tree)
+        .and_then(|root| root.bindings().next())
+        .and_then(|binding| binding.value())
+        .and_then(|term| term.expr())
+        .unwrap()
+}
+
+fn text(node: &impl AstNode) -> String {
+    node.syntax().bare().flatten().trim().to_string()
+}
+
+#[test]
+fn ast_binding() {
+    let syntax_tree = parse("/// The answer\nanswer :: 42\n@import \"other.adept\"\n");
+    let root = ast::Root::cast(syntax_tree).unwrap();
+    let binding = root.bindings().next().unwrap();
+
+    assert_eq!(
+        binding.name().and_then(|name| name.text()).as_deref(),
+        Some("answer")
+    );
+    assert_eq!(binding.doc().as_deref(), Some("The answer"));
+    assert_eq!(
+        binding.value().map(|value| text(&value)).as_deref(),
+        Some("42")
+    );
+
+    let import = root.imports().next().unwrap();
+    assert_eq!(import.path().as_deref(), Some("other.adept"));
+}
+
+#[test]
+fn ast_let() {
+    let ast::Expr::Fn(func) = value("x :: @fn(): Nat {\n    y := 1\n    y\n}\n") else {
+        panic!("expected fn");
+    };
+    let Some(ast::Expr::Let(let_)) = func
+        .body()
+        .and_then(|block| block.terms().next())
+        .and_then(|term| term.expr())
+    else {
+        panic!("expected let");
+    };
+
+    assert_eq!(let_.binder().as_deref(), Some("y"));
+    assert!(let_.ty().is_none());
+    assert_eq!(let_.value().map(|value| text(&value)).as_deref(), Some("1"));
+    assert_eq!(let_.body().map(|body| text(&body)).as_deref(), Some("y"));
+}
+
+#[test]
+fn ast_typed_let() {
+    let ast::Expr::Let(let_) = value("x :: y: Nat = 1\n    y\n") else {
+        panic!("expected let");
+    };
+
+    assert_eq!(let_.binder().as_deref(), Some("y"));
+    assert_eq!(let_.ty().map(|ty| text(&ty)).as_deref(), Some("Nat"));
+    assert_eq!(let_.value().map(|value| text(&value)).as_deref(), Some("1"));
+    assert_eq!(let_.body().map(|body| text(&body)).as_deref(), Some("y"));
+}
+
+#[test]
+fn ast_let_with_empty_type() {
+    let ast::Expr::Let(let_) = value("x :: y: = 1\n    y\n") else {
+        panic!("expected let");
+    };
+
+    assert!(let_.ty().is_none());
+    assert_eq!(let_.value().map(|value| text(&value)).as_deref(), Some("1"));
+    assert_eq!(let_.body().map(|body| text(&body)).as_deref(), Some("y"));
+}
+
+#[test]
+fn ast_fn_params() {
+    let ast::Expr::Fn(func) = value("id :: @fn(x: Nat, y, z: Bool): Nat { x }\n") else {
+        panic!("expected fn");
+    };
+
+    let heads = func
+        .param_list()
+        .into_iter()
+        .flat_map(|list| list.params())
+        .flat_map(|param| param.heads())
+        .map(|head| (head.binder(), head.implicitness().to_implicitness()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        heads,
+        [
+            (Some("x".into()), ast::Implicitness::Explicit),
+            (Some("y".into()), ast::Implicitness::Explicit),
+            (Some("z".into()), ast::Implicitness::Explicit),
+        ]
+    );
+    assert_eq!(
+        func.return_type()
+            .and_then(|ty| ty.ty())
+            .map(|ty| text(&ty))
+            .as_deref(),
+        Some("Nat")
+    );
+    assert_eq!(func.body().map(|body| body.terms().count()), Some(1));
+}
+
+#[test]
+fn ast_match() {
+    let ast::Expr::Match(match_) =
+        value("x :: @match n @so (m) Bool {\n    0 => true\n    @nat_succ(k) => false\n}\n")
+    else {
+        panic!("expected match");
+    };
+
+    assert_eq!(
+        match_.scrutinee().map(|term| text(&term)).as_deref(),
+        Some("n")
+    );
+    assert_eq!(
+        match_
+            .motive()
+            .and_then(|motive| motive.binder())
+            .and_then(|name| name.text())
+            .as_deref(),
+        Some("m")
+    );
+    assert_eq!(match_.eliminator(), Some(ast::Eliminator::NatElim));
+
+    let patterns = match_
+        .arms()
+        .flat_map(|arm| arm.pattern())
+        .collect::<Vec<_>>();
+
+    assert!(matches!(
+        patterns[0].kind(),
+        Some(ast::PatternKind::Integer(_))
+    ));
+    assert!(matches!(
+        patterns[1].kind(),
+        Some(ast::PatternKind::NatSucc(Some(_)))
+    ));
+    assert!(patterns[1].binds("k"));
+    assert!(!patterns[0].binds("k"));
+}
+
+#[test]
+fn ast_variant_pattern() {
+    let ast::Expr::Match(match_) =
+        value("x :: @match v {\n    @variant some(y, _) => y\n    other => 0\n}\n")
+    else {
+        panic!("expected match");
+    };
+
+    let arms = match_.arms().collect::<Vec<_>>();
+    let Some(ast::PatternKind::Variant { name, fields }) =
+        arms[0].pattern().and_then(|pattern| pattern.kind())
+    else {
+        panic!("expected variant pattern");
+    };
+
+    assert_eq!(name.and_then(|name| name.text()).as_deref(), Some("some"));
+    assert_eq!(fields.len(), 2);
+    assert!(arms[0].pattern().unwrap().binds("y"));
+    assert!(arms[1].pattern().unwrap().binds("other"));
+    assert_eq!(match_.eliminator(), Some(ast::Eliminator::VariantElim));
+}
+
+#[test]
+fn ast_record() {
+    let ast::Expr::Record(record) = value("x :: @record(1, y: 2)\n") else {
+        panic!("expected record");
+    };
+
+    let fields = record.fields().collect::<Vec<_>>();
+    assert_eq!(fields.len(), 2);
+    assert!(ast::Term::cast(fields[0].clone()).is_some());
+
+    let field = ast::FieldInit::cast(fields[1].clone()).unwrap();
+    assert_eq!(
+        field.name().and_then(|name| name.text()).as_deref(),
+        Some("y")
+    );
+    assert_eq!(
+        field.value().map(|value| text(&value)).as_deref(),
+        Some("2")
+    );
+}
+
+#[test]
+fn ast_if() {
+    let ast::Expr::If(if_) = value("x :: @if(true, Nat, 1, 2)\n") else {
+        panic!("expected if");
+    };
+
+    assert_eq!(
+        if_.condition().map(|term| text(&term)).as_deref(),
+        Some("true")
+    );
+    assert_eq!(if_.motive().map(|term| text(&term)).as_deref(), Some("Nat"));
+    assert_eq!(
+        if_.then_branch()
+            .and_then(|branch| branch.term())
+            .map(|term| text(&term))
+            .as_deref(),
+        Some("1")
+    );
+}
+
+#[test]
+fn locally_bound_names_are_not_free() {
+    let syntax_tree = parse(
+        "f :: @fn(x: Nat): Nat {\n    y := x\n    @match y {\n        @nat_succ(k) => k + z\n        _ => 0\n    }\n}\n",
+    );
+
+    let free = syntax_tree
+        .free_variables()
+        .map(|(name, _)| name.to_string())
+        .collect::<Vec<_>>();
+
+    assert_eq!(free, ["z"]);
+}
+
+#[test]
+fn ast_implicit_params() {
+    let ast::Expr::Fn(func) = value("id :: @fn($T, x: T, $U u, $ V: Type): T { x }\n") else {
+        panic!("expected fn");
+    };
+
+    let heads = func
+        .param_list()
+        .into_iter()
+        .flat_map(|list| list.params())
+        .flat_map(|param| param.heads())
+        .map(|head| (head.binder(), head.implicitness()))
+        .collect::<Vec<_>>();
+
+    assert!(matches!(
+        heads.as_slice(),
+        [
+            (Some(t), ast::NamedImplicitness::Implicitness(ast::Implicitness::Implicit)),
+            (Some(x), ast::NamedImplicitness::Implicitness(ast::Implicitness::Explicit)),
+            (Some(u), ast::NamedImplicitness::ImplicitWithName(implicit_u)),
+            (Some(v), ast::NamedImplicitness::Implicitness(ast::Implicitness::Implicit)),
+        ] if t.as_ref() == "T"
+            && x.as_ref() == "x"
+            && u.as_ref() == "u"
+            && implicit_u.as_ref() == "U"
+            && v.as_ref() == "V"
+    ));
+}
+
+/// The message of each identifier warning, sorted by position
+fn identifier_warnings(source: &str) -> Vec<String> {
+    let mut warnings = parse(source).identifier_warnings();
+    warnings.sort_by_key(|warning| warning.range.start);
+    warnings
+        .into_iter()
+        .map(|warning| warning.to_string())
+        .collect()
+}
+
+#[test]
+fn identifiers_are_nfc_normalized() {
+    // `é` as a single code point, and as `e` followed by a combining acute accent
+    let syntax_tree = parse("caf\u{e9} :: 1\nx :: cafe\u{301}\n");
+
+    assert_eq!(binding_names(&syntax_tree), ["caf\u{e9}", "x"]);
+    assert_eq!(errors(&syntax_tree), []);
+
+    let variable = syntax_tree
+        .descendants()
+        .find_map(|node| match node.bare().kind() {
+            BareSyntaxKind::Variable(name) => Some((name.clone(), node.bare().flatten())),
+            _ => None,
+        });
+
+    // The name is normalized, while the source text is kept as written
+    assert_eq!(
+        variable,
+        Some(("caf\u{e9}".into(), "cafe\u{301}".to_string()))
+    );
+}
+
+#[test]
+fn canonically_equivalent_identifiers_are_not_confusable() {
+    assert_eq!(
+        identifier_warnings("caf\u{e9} :: 1\nx :: cafe\u{301}\n"),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn confusable_identifiers_warn() {
+    // The second name uses a Cyrillic `а`, and both names are reported
+    assert_eq!(
+        identifier_warnings("a :: 1\n\u{430} :: 2\n"),
+        [
+            "Identifier `a` can be confused with `\u{430}`",
+            "Identifier `\u{430}` can be confused with `a`",
+        ]
+    );
+
+    // Plain ASCII names are never reported, even if they look alike
+    assert_eq!(
+        identifier_warnings("rn :: 1\nm :: 2\n"),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn mixed_script_identifiers_warn() {
+    assert_eq!(
+        identifier_warnings("p\u{430}y :: 1\n"),
+        ["Identifier `p\u{430}y` mixes characters from different scripts"]
+    );
+
+    // Names in a single non-Latin script are fine
+    assert_eq!(
+        identifier_warnings("\u{3b1}\u{3b2} :: 1\n"),
+        Vec::<String>::new()
+    );
+}
+
+fn dump(syntax_tree: &Arc<SyntaxNode>) -> String {
+    let mut output = vec![];
+    syntax_tree.dump(&mut output, 0).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+/// Replaces `range` of the document with `text`, and checks that reparsing only
+/// the edited lines gives the same syntax tree as parsing everything again
+fn assert_reparse_matches(
+    document: &mut Document,
+    lexed: &mut Lexed,
+    range: TextPointRangeUtf16,
+    text: &str,
+) {
+    document.apply_utf16_text_edit(TextEditUtf16 {
+        range,
+        replace_with: text.into(),
+    });
+
+    let start = range.start();
+    let edited = TextPointRangeUtf16::new(start, start + TextPointDiffUtf16::of_str(text));
+    let syntax_tree = reparse(
+        document,
+        Some(lexed),
+        document.translate_utf16_point_range(edited),
+    );
+
+    let expected = reparse(document, None, document.full_range());
+    assert_eq!(dump(&syntax_tree), dump(&expected));
+}
+
+fn point(line: usize, col: usize) -> TextPointUtf16 {
+    TextPointUtf16 {
+        line: text_edit::LineIndex(line),
+        col: text_edit::TextLengthUtf16(col),
+    }
+}
+
+#[test]
+fn reparse_after_edits_matches_full_parse() {
+    let mut document = Document::new("a :: 1\nb :: 2\nc :: 3\n");
+    let mut lexed = Lexed::default();
+    reparse(&document, Some(&mut lexed), document.full_range());
+
+    // Changing a value in the middle
+    let range = TextPointRangeUtf16::new(point(1, 5), point(1, 6));
+    assert_reparse_matches(&mut document, &mut lexed, range, "22");
+
+    // Inserting new bindings
+    let range = TextPointRangeUtf16::new(point(1, 0), point(1, 0));
+    assert_reparse_matches(&mut document, &mut lexed, range, "x :: 4\ny :: 5\n");
+
+    // Opening a comment that swallows the rest of the file
+    let range = TextPointRangeUtf16::new(point(2, 0), point(2, 0));
+    assert_reparse_matches(&mut document, &mut lexed, range, "/* ");
+
+    // Removing the comment and a line
+    let range = TextPointRangeUtf16::new(point(2, 0), point(4, 0));
+    assert_reparse_matches(&mut document, &mut lexed, range, "");
+}
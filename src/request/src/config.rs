@@ -0,0 +1,210 @@
+use crate::Error;
+use std::{iter::Peekable, str::Chars, sync::Arc};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigValue {
+    String(Arc<str>),
+    Integer(u64),
+    Bool(bool),
+    Array(Vec<ConfigValue>),
+    Object(Vec<(Arc<str>, ConfigValue)>),
+}
+
+impl ConfigValue {
+    pub fn parse(content: &str) -> Result<Self, Error> {
+        let mut parser = ConfigParser {
+            chars: content.chars().peekable(),
+        };
+
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+
+        if parser.chars.peek().is_some() {
+            return Err(Error::InvalidProjectConfigSyntax);
+        }
+
+        Ok(value)
+    }
+
+    pub fn as_str(&self) -> Option<&Arc<str>> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<u64> {
+        match self {
+            Self::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(Arc<str>, ConfigValue)]> {
+        match self {
+            Self::Object(fields) => Some(fields),
+            _ => None,
+        }
+    }
+}
+
+struct ConfigParser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> ConfigParser<'a> {
+    fn parse_value(&mut self) -> Result<ConfigValue, Error> {
+        self.skip_whitespace();
+
+        match self.chars.peek().copied() {
+            Some('{') => self.parse_object(),
+            Some('[') => self.parse_array(),
+            Some('"') => self.parse_string().map(ConfigValue::String),
+            Some(c) if c.is_ascii_digit() => self.parse_integer(),
+            Some(c) if is_key_start(c) => match self.parse_key()?.as_ref() {
+                "true" => Ok(ConfigValue::Bool(true)),
+                "false" => Ok(ConfigValue::Bool(false)),
+                _ => Err(Error::InvalidProjectConfigSyntax),
+            },
+            _ => Err(Error::InvalidProjectConfigSyntax),
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<ConfigValue, Error> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+
+            if self.eat('}') {
+                return Ok(ConfigValue::Object(fields));
+            }
+
+            let key = match self.chars.peek() {
+                Some('"') => self.parse_string()?,
+                _ => self.parse_key()?,
+            };
+
+            self.skip_whitespace();
+            self.expect(':')?;
+            fields.push((key, self.parse_value()?));
+            self.skip_whitespace();
+
+            if !self.eat(',') {
+                self.skip_whitespace();
+                self.expect('}')?;
+                return Ok(ConfigValue::Object(fields));
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<ConfigValue, Error> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+
+            if self.eat(']') {
+                return Ok(ConfigValue::Array(items));
+            }
+
+            items.push(self.parse_value()?);
+            self.skip_whitespace();
+
+            if !self.eat(',') {
+                self.skip_whitespace();
+                self.expect(']')?;
+                return Ok(ConfigValue::Array(items));
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<Arc<str>, Error> {
+        self.expect('"')?;
+        let mut value = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(value.into()),
+                Some('\\') => match self.chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(c @ ('"' | '\\' | '/')) => value.push(c),
+                    _ => return Err(Error::InvalidProjectConfigSyntax),
+                },
+                Some(c) => value.push(c),
+                None => return Err(Error::ExpectedChar('"')),
+            }
+        }
+    }
+
+    fn parse_integer(&mut self) -> Result<ConfigValue, Error> {
+        let mut digits = String::new();
+
+        while let Some(c) = self.chars.next_if(|c| c.is_ascii_digit() || *c == '_') {
+            if c != '_' {
+                digits.push(c);
+            }
+        }
+
+        digits
+            .parse()
+            .map(ConfigValue::Integer)
+            .map_err(|_| Error::InvalidProjectConfigSyntax)
+    }
+
+    fn parse_key(&mut self) -> Result<Arc<str>, Error> {
+        let mut key = String::new();
+
+        while let Some(c) = self
+            .chars
+            .next_if(|c| is_key_start(*c) || c.is_ascii_digit())
+        {
+            key.push(c);
+        }
+
+        if key.is_empty() {
+            return Err(Error::InvalidProjectConfigSyntax);
+        }
+
+        Ok(key.into())
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+            // Single-line comments are allowed anywhere whitespace is
+            if self.chars.clone().take(2).eq("//".chars()) {
+                while self.chars.next_if(|c| *c != '\n').is_some() {}
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        self.chars.next_if_eq(&expected).is_some()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), Error> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(Error::ExpectedChar(expected))
+        }
+    }
+}
+
+fn is_key_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
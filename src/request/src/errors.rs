@@ -4,6 +4,7 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
+use text_edit::TextPointRangeUtf16;
 use thiserror::Error;
 use vfs::Canonical;
 
@@ -33,4 +34,107 @@ pub enum Error {
     FailedToCanonicalize(Arc<Path>),
     #[error("Failed to open file `{0}`")]
     FailedToOpenFile(Arc<Canonical<PathBuf>>),
+    #[error("Failed to find imported file `{0}`")]
+    FailedToResolveImport(Arc<str>),
+    #[error("Undeclared name `{0}`")]
+    UndeclaredName(Arc<str>),
+    #[error("Failed to find dependency `{0}`")]
+    FailedToResolveDependency(Arc<str>),
+    #[error("Cyclic dependency `{0}`")]
+    CyclicDependency(Arc<str>),
+    #[error("Failed to write `adept.lock`")]
+    FailedToWriteLockFile,
+    #[error("{0}")]
+    Syntax(Arc<str>),
+    #[error("No field `{0}` in record")]
+    UnknownField(Arc<str>),
+    #[error("Duplicate field `{0}`")]
+    DuplicateField(Arc<str>),
+    #[error("Record fields must be either all named or all positional")]
+    MixedRecordFields,
+    #[error("Identifier `{0}` mixes characters from different scripts")]
+    MixedScriptIdentifier(Arc<str>),
+    #[error("Identifier `{0}` can be confused with `{1}`")]
+    ConfusableIdentifier(Arc<str>, Arc<str>),
+    #[error("{1}")]
+    At(SourceLocation, Arc<Error>),
+}
+
+impl Error {
+    pub fn at(self, location: SourceLocation) -> Self {
+        Self::At(location, Arc::new(self))
+    }
+
+    /// Stable identifier for each kind of error. These are part of the
+    /// machine-readable diagnostics format, so they must never be reused.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::MissingProjectFile => "E0001",
+            Self::ProjectFileMustBeText => "E0002",
+            Self::FailedToOpenProjectFile => "E0003",
+            Self::ExpectedChar(_) => "E0004",
+            Self::InvalidProjectConfigSyntax => "E0005",
+            Self::MissingRootFileInProjectConfig => "E0006",
+            Self::UnsupportedAdeptVersion => "E0007",
+            Self::InvalidProjectConfigOption(_) => "E0008",
+            Self::FailedToCanonicalize(_) => "E0009",
+            Self::FailedToOpenFile(_) => "E0010",
+            Self::FailedToResolveImport(_) => "E0011",
+            Self::UndeclaredName(_) => "E0012",
+            Self::FailedToResolveDependency(_) => "E0013",
+            Self::CyclicDependency(_) => "E0014",
+            Self::FailedToWriteLockFile => "E0015",
+            Self::Syntax(_) => "E0016",
+            Self::UnknownField(_) => "E0017",
+            Self::DuplicateField(_) => "E0018",
+            Self::MixedRecordFields => "E0019",
+            Self::MixedScriptIdentifier(_) => "E0020",
+            Self::ConfusableIdentifier(..) => "E0021",
+            Self::At(_, inner) => inner.code(),
+        }
+    }
+
+    pub fn location(&self) -> Option<&SourceLocation> {
+        match self {
+            Self::At(location, _) => Some(location),
+            _ => None,
+        }
+    }
+
+    /// Warnings are reported like errors, but don't cause compilation to fail
+    pub fn is_warning(&self) -> bool {
+        matches!(
+            self.without_location(),
+            Self::MixedScriptIdentifier(_) | Self::ConfusableIdentifier(..)
+        )
+    }
+
+    pub fn without_location(&self) -> &Self {
+        match self {
+            Self::At(_, inner) => inner.without_location(),
+            _ => self,
+        }
+    }
+}
+
+/// Zero-based lines and UTF-16 columns within a file
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SourceLocation {
+    pub filename: Arc<Canonical<PathBuf>>,
+    pub start_line: usize,
+    pub start_col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+}
+
+impl SourceLocation {
+    pub fn new(filename: Arc<Canonical<PathBuf>>, range: TextPointRangeUtf16) -> Self {
+        Self {
+            filename,
+            start_line: range.start.line.0,
+            start_col: range.start.col.0,
+            end_line: range.end.line.0,
+            end_col: range.end.col.0,
+        }
+    }
 }
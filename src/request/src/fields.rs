@@ -0,0 +1,191 @@
+use crate::Error;
+use std::{collections::HashSet, sync::Arc};
+use syntax_tree::{
+    SyntaxNode,
+    ast::{self, AstNode},
+};
+use text_edit::TextPointRangeUtf16;
+
+/// How many variables are followed to find the record that a field is accessed on,
+/// which also stops at bindings that refer to each other
+const MAX_INDIRECTION: usize = 16;
+
+/// Finds errors in record values, record types and field accesses,
+/// as far as they can be found without type checking
+pub fn check_fields(syntax_tree: &Arc<SyntaxNode>) -> Vec<(Error, TextPointRangeUtf16)> {
+    let mut errors = Vec::new();
+
+    for node in syntax_tree.descendants() {
+        if let Some(record) = ast::RecordValue::cast(node.clone()) {
+            check_record_value(&record, &mut errors);
+        } else if let Some(record_type) = ast::RecordType::cast(node.clone()) {
+            let names = record_type
+                .field_defs()
+                .flat_map(|field_def| field_def.names());
+            check_duplicates(names, &mut errors);
+        } else if let Some(access) = ast::FieldAccess::cast(node)
+            && let Some(Err(error)) = field_index(&access)
+        {
+            let range = access
+                .field()
+                .map(|field| field.syntax().text_range())
+                .unwrap_or_else(|| access.syntax().text_range());
+            errors.push((error, range));
+        }
+    }
+
+    errors.sort_by_key(|(_, range)| range.start);
+    errors
+}
+
+fn check_record_value(record: &ast::RecordValue, errors: &mut Vec<(Error, TextPointRangeUtf16)>) {
+    let (named, positional): (Vec<_>, Vec<_>) = record
+        .fields()
+        .partition(|field| ast::FieldInit::can_cast(field.bare().kind()));
+
+    if !named.is_empty() && !positional.is_empty() {
+        errors.push((Error::MixedRecordFields, record.syntax().text_range()));
+    }
+
+    let names = named
+        .into_iter()
+        .flat_map(ast::FieldInit::cast)
+        .flat_map(|field| field.name());
+    check_duplicates(names, errors);
+}
+
+fn check_duplicates(
+    names: impl Iterator<Item = ast::Name>,
+    errors: &mut Vec<(Error, TextPointRangeUtf16)>,
+) {
+    let mut seen = HashSet::new();
+
+    for name in names {
+        if let Some(text) = name.text()
+            && !seen.insert(text.clone())
+        {
+            errors.push((Error::DuplicateField(text), name.syntax().text_range()));
+        }
+    }
+}
+
+/// Resolves `.name` to the position of the field, which is what it projects out of the record.
+/// Gives `None` when the fields of the record aren't known without type checking.
+pub fn field_index(access: &ast::FieldAccess) -> Option<Result<usize, Error>> {
+    let name = access.field()?.text()?;
+    let fields = record_field_names(&access.record()?, MAX_INDIRECTION)?;
+
+    Some(
+        fields
+            .iter()
+            .position(|field| *field == name)
+            .ok_or(Error::UnknownField(name)),
+    )
+}
+
+/// The names of the fields of the record that `term` evaluates to, in order
+fn record_field_names(term: &ast::Term, depth: usize) -> Option<Vec<Arc<str>>> {
+    match term.expr()? {
+        ast::Expr::Parenthesized(inner) => record_field_names(&inner.term()?, depth),
+        ast::Expr::Record(record) => {
+            let fields = record.fields().collect::<Vec<_>>();
+
+            let names = fields
+                .iter()
+                .flat_map(|field| ast::FieldInit::cast(field.clone()))
+                .flat_map(|field| field.name()?.text())
+                .collect::<Vec<_>>();
+
+            // Records that mix positional and named fields are already an error
+            (names.len() == fields.len() || names.is_empty()).then_some(names)
+        }
+        ast::Expr::Variable(variable) if depth > 0 => {
+            match definition(variable.syntax(), &variable.name())? {
+                Definition::Type(ty) => record_type_field_names(&ty),
+                Definition::Value(value) => record_field_names(&value, depth - 1),
+            }
+        }
+        _ => None,
+    }
+}
+
+fn record_type_field_names(ty: &ast::Term) -> Option<Vec<Arc<str>>> {
+    match ty.expr()? {
+        ast::Expr::Parenthesized(inner) => record_type_field_names(&inner.term()?),
+        ast::Expr::RecordType(record_type) => record_type
+            .field_defs()
+            .flat_map(|field_def| field_def.names())
+            .map(|name| name.text())
+            .collect(),
+        _ => None,
+    }
+}
+
+/// What is known about the value of a variable where it is bound
+enum Definition {
+    Type(ast::Term),
+    Value(ast::Term),
+}
+
+/// Finds the innermost binder of `name` around `node`, or else the binding in the same file
+fn definition(node: &Arc<SyntaxNode>, name: &str) -> Option<Definition> {
+    let mut ancestor = node.parent().cloned();
+
+    while let Some(node) = ancestor {
+        if let Some(let_) = ast::Let::cast(node.clone())
+            && let_.binder().as_deref() == Some(name)
+        {
+            return match let_.ty() {
+                Some(ty) => Some(Definition::Type(ty)),
+                None => let_.value().map(Definition::Value),
+            };
+        }
+
+        let param_list = match ast::FnValue::cast(node.clone()) {
+            Some(func) => func.param_list(),
+            None => ast::FnType::cast(node.clone()).and_then(|func| func.param_list()),
+        };
+
+        if let Some(param) = param_list
+            .into_iter()
+            .flat_map(|list| list.params())
+            .find(|param| {
+                param
+                    .heads()
+                    .any(|head| head.binder().as_deref() == Some(name))
+            })
+        {
+            return param
+                .type_annotation()
+                .and_then(|annotation| annotation.ty())
+                .map(Definition::Type);
+        }
+
+        // Patterns and motives bind values whose fields aren't known from the syntax
+        let binds_in_match = ast::MatchArm::cast(node.clone())
+            .and_then(|arm| arm.pattern())
+            .is_some_and(|pattern| pattern.binds(name))
+            || ast::MatchMotive::cast(node.clone())
+                .and_then(|motive| motive.binder())
+                .and_then(|binder| binder.text())
+                .is_some_and(|binder| binder.as_ref() == name);
+
+        if binds_in_match {
+            return None;
+        }
+
+        if let Some(root) = ast::Root::cast(node.clone()) {
+            return root
+                .bindings()
+                .find(|binding| {
+                    binding.name().and_then(|binding| binding.text()).as_deref() == Some(name)
+                })
+                .and_then(|binding| binding.value())
+                .map(Definition::Value);
+        }
+
+        ancestor = node.parent().cloned();
+    }
+
+    None
+}
@@ -1,8 +1,12 @@
 mod block_on;
+mod config;
 mod errors;
+mod fields;
 mod is_div;
 mod like;
 mod pf;
+mod profile;
+mod project;
 mod rt;
 mod run;
 mod succ;
@@ -11,21 +15,21 @@ mod task;
 mod top_errors;
 mod un_like;
 mod unblock;
+mod unit_tests;
 
 pub use block_on::*;
 use by_address::ByAddress;
+pub use config::*;
 pub use errors::*;
+pub use fields::*;
 pub use is_div::*;
 pub use like::*;
 pub use pf::*;
+pub use profile::*;
+pub use project::*;
 pub use requests::*;
 pub use rt::*;
-use serde::{Deserialize, Serialize};
-use std::{
-    marker::PhantomData,
-    path::{Path, PathBuf},
-    sync::Arc,
-};
+use std::{marker::PhantomData, path::PathBuf, sync::Arc};
 pub use succ::*;
 pub use syms::*;
 use syntax_tree::SyntaxNode;
@@ -48,14 +52,6 @@ macro_rules! rt_trace {
     }};
 }
 
-#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
-pub struct Project {
-    pub root: Arc<Path>,
-    pub interval_ms: Option<u64>,
-    pub max_idle_time_ms: Option<u64>,
-    pub cache_to_disk: Option<bool>,
-}
-
 #[define_requests::group]
 mod requests {
     use super::*;
@@ -75,9 +71,62 @@ mod requests {
     #[derive(Default)]
     pub struct ParseFileState;
 
+    #[define_requests::returns(WithErrors<Arc<[Arc<Canonical<PathBuf>>]>>)]
+    pub struct Imports {
+        pub filename: Arc<Canonical<PathBuf>>,
+    }
+    #[derive(Default)]
+    pub struct ImportsState;
+
+    #[define_requests::returns(Option<Arc<Canonical<PathBuf>>>)]
+    pub struct ResolveSymbol {
+        pub filename: Arc<Canonical<PathBuf>>,
+        pub name: Arc<str>,
+    }
+    #[derive(Default)]
+    pub struct ResolveSymbolState;
+
+    #[define_requests::returns(WithErrors<Option<Arc<Project>>>)]
+    pub struct GetProject {
+        pub directory: Arc<Canonical<PathBuf>>,
+    }
+    #[derive(Default)]
+    pub struct GetProjectState;
+
+    #[define_requests::returns(WithErrors<Option<Arc<BuildConfig>>>)]
+    pub struct GetBuildConfig {
+        pub directory: Arc<Canonical<PathBuf>>,
+        pub options: BuildOptions,
+    }
+    #[derive(Default)]
+    pub struct GetBuildConfigState;
+
+    #[define_requests::returns(WithErrors<Arc<[Package]>>)]
+    pub struct ResolveDependencies {
+        pub directory: Arc<Canonical<PathBuf>>,
+    }
+    #[derive(Default)]
+    pub struct ResolveDependenciesState;
+
+    #[define_requests::returns(WithErrors<Arc<[Arc<Canonical<PathBuf>>]>>)]
+    pub struct PackageFiles {
+        pub directory: Arc<Canonical<PathBuf>>,
+    }
+    #[derive(Default)]
+    pub struct PackageFilesState;
+
+    #[define_requests::returns(Option<Arc<Canonical<PathBuf>>>)]
+    pub struct ResolvePackageSymbol {
+        pub directory: Arc<Canonical<PathBuf>>,
+        pub name: Arc<str>,
+    }
+    #[derive(Default)]
+    pub struct ResolvePackageSymbolState;
+
     #[define_requests::returns(WithErrors<Arc<[String]>>)]
     pub struct ListSymbols {
         pub filename: Arc<Canonical<PathBuf>>,
+        pub options: BuildOptions,
     }
     #[derive(Default)]
     pub struct ListSymbolsState;
@@ -0,0 +1,129 @@
+use crate::{ConfigValue, Error, Project};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, sync::Arc};
+
+#[derive(
+    Copy, Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub enum ProfileName {
+    #[default]
+    Debug,
+    Release,
+}
+
+impl ProfileName {
+    pub fn parse(name: &str) -> Result<Self, Error> {
+        match name {
+            "debug" => Ok(Self::Debug),
+            "release" => Ok(Self::Release),
+            _ => Err(Error::InvalidProjectConfigOption(name.into())),
+        }
+    }
+}
+
+impl Display for ProfileName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Debug => f.write_str("debug"),
+            Self::Release => f.write_str("release"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    pub optimization: u8,
+    pub debug_info: bool,
+}
+
+impl Profile {
+    pub fn default_for(name: ProfileName) -> Self {
+        match name {
+            ProfileName::Debug => Self {
+                optimization: 0,
+                debug_info: true,
+            },
+            ProfileName::Release => Self {
+                optimization: 3,
+                debug_info: false,
+            },
+        }
+    }
+
+    pub fn parse(name: ProfileName, value: &ConfigValue) -> Result<Self, Error> {
+        let mut profile = Self::default_for(name);
+        let fields = value
+            .as_object()
+            .ok_or_else(|| Error::InvalidProjectConfigOption(format!("profiles.{name}").into()))?;
+
+        for (key, value) in fields {
+            let invalid =
+                || Error::InvalidProjectConfigOption(format!("profiles.{name}.{key}").into());
+
+            match key.as_ref() {
+                "optimization" => {
+                    profile.optimization = value
+                        .as_integer()
+                        .filter(|level| *level <= 3)
+                        .ok_or_else(invalid)? as u8
+                }
+                "debug_info" => profile.debug_info = value.as_bool().ok_or_else(invalid)?,
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(profile)
+    }
+}
+
+/// Build settings requested by the user, usually from the command line
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildOptions {
+    pub profile: Option<ProfileName>,
+    pub target: Option<Arc<str>>,
+}
+
+/// Build settings after combining the project defaults with the requested options
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildConfig {
+    pub profile_name: ProfileName,
+    pub profile: Profile,
+    pub target: Arc<str>,
+}
+
+impl BuildConfig {
+    pub fn new(project: Option<&Project>, options: &BuildOptions) -> Self {
+        let profile_name = options
+            .profile
+            .or_else(|| project.map(|project| project.profile))
+            .unwrap_or_default();
+
+        let profile = project
+            .map(|project| project.profile(profile_name))
+            .unwrap_or_else(|| Profile::default_for(profile_name));
+
+        let target = options
+            .target
+            .clone()
+            .or_else(|| project.and_then(|project| project.target.clone()))
+            .unwrap_or_else(host_target);
+
+        Self {
+            profile_name,
+            profile,
+            target,
+        }
+    }
+}
+
+pub fn host_target() -> Arc<str> {
+    let arch = std::env::consts::ARCH;
+
+    match std::env::consts::OS {
+        "linux" => format!("{arch}-unknown-linux-gnu"),
+        "macos" => format!("{arch}-apple-darwin"),
+        "windows" => format!("{arch}-pc-windows-gnu"),
+        os => format!("{arch}-unknown-{os}"),
+    }
+    .into()
+}
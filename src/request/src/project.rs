@@ -0,0 +1,206 @@
+use crate::{ConfigValue, Error, Profile, ProfileName};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+use vfs::Canonical;
+
+pub const PROJECT_FILENAME: &str = "adept.build";
+pub const LOCK_FILENAME: &str = "adept.lock";
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Project {
+    pub root: Arc<Path>,
+    pub name: Option<Arc<str>>,
+    pub version: Option<Arc<str>>,
+    pub main: Arc<Path>,
+    pub dependencies: Arc<[Dependency]>,
+    pub profile: ProfileName,
+    pub target: Option<Arc<str>>,
+    pub debug: Profile,
+    pub release: Profile,
+    pub interval_ms: Option<u64>,
+    pub max_idle_time_ms: Option<u64>,
+    pub cache_to_disk: Option<bool>,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dependency {
+    pub name: Arc<str>,
+    pub path: Arc<Path>,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Package {
+    pub directory: Arc<Canonical<PathBuf>>,
+    pub project: Arc<Project>,
+    pub dependencies: Arc<[ResolvedDependency]>,
+}
+
+pub type ResolvedDependency = (Arc<str>, Arc<Canonical<PathBuf>>);
+
+impl Project {
+    pub fn parse(root: Arc<Path>, content: &str) -> Result<Self, Error> {
+        let config = ConfigValue::parse(content)?;
+        let fields = config
+            .as_object()
+            .ok_or(Error::InvalidProjectConfigSyntax)?;
+
+        let mut adept_version = None;
+        let mut name = None;
+        let mut version = None;
+        let mut main = None;
+        let mut dependencies = Vec::new();
+        let mut profile = ProfileName::default();
+        let mut target = None;
+        let mut debug = Profile::default_for(ProfileName::Debug);
+        let mut release = Profile::default_for(ProfileName::Release);
+        let mut interval_ms = None;
+        let mut max_idle_time_ms = None;
+        let mut cache_to_disk = None;
+
+        for (key, value) in fields {
+            let invalid = || Error::InvalidProjectConfigOption(key.clone());
+
+            match key.as_ref() {
+                "adept" => adept_version = Some(value.as_str().ok_or_else(invalid)?.clone()),
+                "name" => name = Some(value.as_str().ok_or_else(invalid)?.clone()),
+                "version" => version = Some(value.as_str().ok_or_else(invalid)?.clone()),
+                "main" => main = Some(value.as_str().ok_or_else(invalid)?.clone()),
+                "profile" => profile = ProfileName::parse(value.as_str().ok_or_else(invalid)?)?,
+                "target" => target = Some(value.as_str().ok_or_else(invalid)?.clone()),
+                "profiles" => {
+                    for (name, settings) in value.as_object().ok_or_else(invalid)? {
+                        match ProfileName::parse(name)? {
+                            ProfileName::Debug => {
+                                debug = Profile::parse(ProfileName::Debug, settings)?
+                            }
+                            ProfileName::Release => {
+                                release = Profile::parse(ProfileName::Release, settings)?
+                            }
+                        }
+                    }
+                }
+                "interval_ms" => interval_ms = Some(value.as_integer().ok_or_else(invalid)?),
+                "max_idle_time_ms" => {
+                    max_idle_time_ms = Some(value.as_integer().ok_or_else(invalid)?)
+                }
+                "cache_to_disk" => cache_to_disk = Some(value.as_bool().ok_or_else(invalid)?),
+                "dependencies" => {
+                    for (name, dependency) in value.as_object().ok_or_else(invalid)? {
+                        dependencies.push(Dependency::parse(name, dependency)?);
+                    }
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        if adept_version.as_deref() != Some("3.0") {
+            return Err(Error::UnsupportedAdeptVersion);
+        }
+
+        let main = main.ok_or(Error::MissingRootFileInProjectConfig)?;
+
+        Ok(Self {
+            root,
+            name,
+            version,
+            main: Path::new(main.as_ref()).into(),
+            dependencies: dependencies.into(),
+            profile,
+            target,
+            debug,
+            release,
+            interval_ms,
+            max_idle_time_ms,
+            cache_to_disk,
+        })
+    }
+}
+
+impl Project {
+    pub fn profile(&self, name: ProfileName) -> Profile {
+        match name {
+            ProfileName::Debug => self.debug,
+            ProfileName::Release => self.release,
+        }
+    }
+}
+
+impl Dependency {
+    fn parse(name: &Arc<str>, value: &ConfigValue) -> Result<Self, Error> {
+        let invalid = || Error::InvalidProjectConfigOption(format!("dependencies.{name}").into());
+
+        // Both `math: "../math"` and `math: { path: "../math" }` are accepted
+        let path = match value {
+            ConfigValue::String(path) => path,
+            ConfigValue::Object(fields) => match &fields[..] {
+                [(key, ConfigValue::String(path))] if key.as_ref() == "path" => path,
+                _ => return Err(invalid()),
+            },
+            _ => return Err(invalid()),
+        };
+
+        Ok(Self {
+            name: name.clone(),
+            path: Path::new(path.as_ref()).into(),
+        })
+    }
+}
+
+/// The contents of `adept.lock`, which records where each package of a build was found
+pub fn lockfile(root: &Canonical<PathBuf>, packages: &[Package]) -> String {
+    let packages = packages
+        .iter()
+        .map(|package| {
+            let dependencies = package
+                .dependencies
+                .iter()
+                .map(|(name, directory)| {
+                    (
+                        name.to_string(),
+                        serde_json::Value::from(relative_path(root, directory)),
+                    )
+                })
+                .collect::<serde_json::Map<_, _>>();
+
+            serde_json::json!({
+                "name": package.project.name.as_deref(),
+                "version": package.project.version.as_deref(),
+                "path": relative_path(root, &package.directory),
+                "dependencies": dependencies,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let lockfile = serde_json::json!({
+        "version": 1,
+        "packages": packages,
+    });
+
+    let mut content = serde_json::to_string_pretty(&lockfile).expect("lockfile is serializable");
+    content.push('\n');
+    content
+}
+
+fn relative_path(base: &Path, path: &Path) -> String {
+    let base = base.components().collect::<Vec<_>>();
+    let path = path.components().collect::<Vec<_>>();
+
+    let common = base
+        .iter()
+        .zip(path.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let relative = std::iter::repeat_n(Component::ParentDir, base.len() - common)
+        .chain(path[common..].iter().copied())
+        .collect::<PathBuf>();
+
+    if relative.as_os_str().is_empty() {
+        ".".into()
+    } else {
+        relative.to_string_lossy().into()
+    }
+}
@@ -1,6 +1,10 @@
 use crate::{BlockOn, Pf, Req, ShouldUnblock, Suspend, Task, TopErrorsNode, UnLike, UnwrapAft};
 use connection::Connection;
-use std::{path::Path, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use vfs::Canonical;
 
 pub enum QueryMode {
     New,
@@ -34,6 +38,12 @@ where
     where
         R: Into<Req> + UnwrapAft<'e, P>;
     fn read_file<'th, 'a>(&'th mut self, filename: &'a Path) -> Result<Arc<str>, ()>;
+
+    /// Requests must look at the file system through these instead of `std::fs`,
+    /// so that what they find is an input of the query like the files they read
+    fn is_file(&mut self, path: &Path) -> bool;
+    fn is_dir(&mut self, path: &Path) -> bool;
+    fn canonicalize(&mut self, path: &Path) -> Option<Canonical<PathBuf>>;
 }
 
 pub trait Ch<'e, P: Pf> {
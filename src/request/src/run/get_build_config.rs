@@ -0,0 +1,28 @@
+use crate::{
+    BuildConfig, GetBuildConfig, GetProject, Like, Pf, Run, Suspend, Th, UnwrapSt, WithErrors,
+};
+use std::sync::Arc;
+
+impl<'e, P: Pf> Run<'e, P> for GetBuildConfig {
+    fn run(
+        &self,
+        _aft: Option<&Self::Aft<'e>>,
+        st: &mut P::St<'e>,
+        th: &mut impl Th<'e, P>,
+    ) -> Result<Self::Aft<'e>, Suspend> {
+        let _st = Self::unwrap_st(st.like_mut());
+
+        let project = th.demand(GetProject {
+            directory: self.directory.clone(),
+        })?;
+
+        let Some(project) = project.value.as_ref() else {
+            return Ok(WithErrors::new(None, project.errors.clone()));
+        };
+
+        Ok(WithErrors::no_errors(Some(Arc::new(BuildConfig::new(
+            Some(project),
+            &self.options,
+        )))))
+    }
+}
@@ -0,0 +1,32 @@
+use crate::{
+    Error, GetProject, Like, PROJECT_FILENAME, Pf, Project, Run, Suspend, Th, UnwrapSt, WithErrors,
+};
+use std::{path::Path, sync::Arc};
+
+impl<'e, P: Pf> Run<'e, P> for GetProject {
+    fn run(
+        &self,
+        _aft: Option<&Self::Aft<'e>>,
+        st: &mut P::St<'e>,
+        th: &mut impl Th<'e, P>,
+    ) -> Result<Self::Aft<'e>, Suspend> {
+        let _st = Self::unwrap_st(st.like_mut());
+
+        let filename = self.directory.join(PROJECT_FILENAME);
+
+        if !th.is_file(&filename) {
+            return Ok(WithErrors::new_one(None, Error::MissingProjectFile));
+        }
+
+        let Ok(content) = th.read_file(&filename) else {
+            return Ok(WithErrors::new_one(None, Error::FailedToOpenProjectFile));
+        };
+
+        let root: Arc<Path> = self.directory.as_ref().as_ref().into();
+
+        Ok(match Project::parse(root, &content) {
+            Ok(project) => WithErrors::no_errors(Some(Arc::new(project))),
+            Err(error) => WithErrors::new_one(None, error),
+        })
+    }
+}
@@ -0,0 +1,41 @@
+use crate::{Error, Imports, Like, ParseFile, Pf, Run, Suspend, Th, UnwrapSt, WithErrors};
+use std::sync::Arc;
+use syntax_tree::ast::{self, AstNode};
+
+impl<'e, P: Pf> Run<'e, P> for Imports {
+    fn run(
+        &self,
+        _aft: Option<&Self::Aft<'e>>,
+        st: &mut P::St<'e>,
+        th: &mut impl Th<'e, P>,
+    ) -> Result<Self::Aft<'e>, Suspend> {
+        let _st = Self::unwrap_st(st.like_mut());
+
+        let parsed = th.demand(ParseFile {
+            filename: self.filename.clone(),
+        })?;
+
+        let mut files = Vec::new();
+        let mut errors = Vec::new();
+
+        // Imports without a filename are already reported as syntax errors
+        let paths = parsed
+            .value
+            .iter()
+            .flat_map(|parsed| ast::Root::cast(parsed.0.clone()))
+            .flat_map(|root| root.imports())
+            .flat_map(|import| import.path())
+            .collect::<Vec<_>>();
+
+        for path in paths {
+            let directory = self.filename.parent().unwrap_or(&self.filename);
+
+            match th.canonicalize(&directory.join(path.as_ref())) {
+                Some(filename) => files.push(Arc::new(filename)),
+                None => errors.push(Error::FailedToResolveImport(path)),
+            }
+        }
+
+        Ok(WithErrors::new(files.into(), errors.into_iter().collect()))
+    }
+}
@@ -1,4 +1,15 @@
-use crate::{Like, ListSymbols, Pf, Run, Suspend, Th, UnwrapSt, WithErrors};
+use crate::{
+    Error, GetBuildConfig, Imports, Like, ListSymbols, PackageFiles, ParseFile, Pf,
+    ResolveDependencies, ResolvePackageSymbol, ResolveSymbol, ResolvedDependency, Run,
+    SourceLocation, Suspend, Th, UnwrapSt, WithErrors, check_fields,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::Arc,
+};
+use syntax_tree::ast::{self, AstNode};
+use vfs::Canonical;
 
 impl<'e, P: Pf> Run<'e, P> for ListSymbols {
     fn run(
@@ -9,16 +20,145 @@ impl<'e, P: Pf> Run<'e, P> for ListSymbols {
     ) -> Result<Self::Aft<'e>, Suspend> {
         let _st = Self::unwrap_st(st.like_mut());
 
-        let parsed = th.demand(crate::ParseFile {
-            filename: self.filename.clone(),
+        let mut names = Vec::new();
+        let mut errors = Vec::new();
+
+        // Directories are treated as packages described by their `adept.build`
+        if th.is_dir(&self.filename) {
+            // Depending on the build config means that switching profile or target
+            // invalidates the result. Problems with the project itself are
+            // reported when resolving dependencies.
+            th.demand(GetBuildConfig {
+                directory: self.filename.clone(),
+                options: self.options.clone(),
+            })?;
+
+            let packages = th
+                .demand(ResolveDependencies {
+                    directory: self.filename.clone(),
+                })?
+                .clone();
+            errors.extend(packages.errors.iter_unordered().cloned());
+
+            // Symbols of a dependency are listed under the key that source code uses to refer
+            // to it, which the root package gets to choose before any other package
+            let mut namespaces = HashMap::new();
+            for (name, directory) in packages
+                .value
+                .iter()
+                .rev()
+                .flat_map(|package| package.dependencies.iter())
+            {
+                namespaces.entry(directory.clone()).or_insert(name.clone());
+            }
+
+            for package in packages.value.iter() {
+                let prefix = match namespaces.get(&package.directory) {
+                    Some(namespace) if package.directory != self.filename => {
+                        format!("{namespace}::")
+                    }
+                    _ => String::new(),
+                };
+
+                let files = th
+                    .demand(PackageFiles {
+                        directory: package.directory.clone(),
+                    })?
+                    .clone();
+                errors.extend(files.errors.iter_unordered().cloned());
+
+                for filename in files.value.iter() {
+                    let symbols = check_file(th, filename, &package.dependencies, &mut errors)?;
+                    names.extend(symbols.into_iter().map(|name| format!("{prefix}{name}")));
+                }
+            }
+
+            return Ok(WithErrors::new(names.into(), errors.into_iter().collect()));
+        }
+
+        let mut visited = HashSet::new();
+        let mut pending = vec![self.filename.clone()];
+
+        while let Some(filename) = pending.pop() {
+            if !visited.insert(filename.clone()) {
+                continue;
+            }
+
+            names.extend(check_file(th, &filename, &[], &mut errors)?);
+
+            let imports = th.demand(Imports {
+                filename: filename.clone(),
+            })?;
+            errors.extend(imports.errors.iter_unordered().cloned());
+            pending.extend(imports.value.iter().rev().cloned());
+        }
+
+        Ok(WithErrors::new(names.into(), errors.into_iter().collect()))
+    }
+}
+
+fn check_file<'e, P: Pf>(
+    th: &mut impl Th<'e, P>,
+    filename: &Arc<Canonical<PathBuf>>,
+    dependencies: &[ResolvedDependency],
+    errors: &mut Vec<Error>,
+) -> Result<Vec<String>, Suspend> {
+    let parsed = th.demand(ParseFile {
+        filename: filename.clone(),
+    })?;
+    errors.extend(parsed.errors.iter_unordered().cloned());
+
+    let Some(syntax_tree) = parsed.value.as_ref().map(|parsed| parsed.0.clone()) else {
+        return Ok(vec![]);
+    };
+
+    let names = ast::Root::cast(syntax_tree.clone())
+        .into_iter()
+        .flat_map(|root| root.bindings())
+        .flat_map(|binding| binding.name().and_then(|name| name.text()))
+        .map(|name| name.to_string())
+        .collect();
+
+    for (error, range) in check_fields(&syntax_tree) {
+        errors.push(error.at(SourceLocation::new(filename.clone(), range)));
+    }
+
+    let mut undeclared = HashSet::new();
+
+    for (name, node) in syntax_tree.free_variables() {
+        let resolved = th.demand(ResolveSymbol {
+            filename: filename.clone(),
+            name: name.clone(),
         })?;
 
-        let names = parsed.value.as_ref().into_iter().flat_map(|parsed| {
-            parsed
-                .bindings()
-                .flat_map(|binding| binding.name.as_ref().map(|s| s.to_string()))
-        });
+        if resolved.is_none() && undeclared.insert(name.clone()) {
+            let location = SourceLocation::new(filename.clone(), node.text_range());
+            errors.push(Error::UndeclaredName(name).at(location));
+        }
+    }
+
+    for (namespace, name, node) in syntax_tree.qualified_variables() {
+        let dependency = dependencies
+            .iter()
+            .find(|(dependency, _)| *dependency == namespace);
 
-        Ok(WithErrors::no_errors(names.collect()))
+        let resolved = match dependency {
+            Some((_, directory)) => th
+                .demand(ResolvePackageSymbol {
+                    directory: directory.clone(),
+                    name: name.clone(),
+                })?
+                .is_some(),
+            None => false,
+        };
+
+        let qualified: Arc<str> = format!("{namespace}::{name}").into();
+
+        if !resolved && undeclared.insert(qualified.clone()) {
+            let location = SourceLocation::new(filename.clone(), node.text_range());
+            errors.push(Error::UndeclaredName(qualified).at(location));
+        }
     }
+
+    Ok(names)
 }
@@ -1,4 +1,11 @@
 mod compile;
+mod get_build_config;
+mod get_project;
+mod imports;
 mod list_symbols;
+mod package_files;
 mod parse_file;
+mod resolve_dependencies;
+mod resolve_package_symbol;
+mod resolve_symbol;
 mod unused_request;
@@ -0,0 +1,53 @@
+use crate::{
+    Error, GetProject, Imports, Like, PackageFiles, Pf, Run, Suspend, Th, UnwrapSt, WithErrors,
+};
+use std::{collections::HashSet, sync::Arc};
+
+impl<'e, P: Pf> Run<'e, P> for PackageFiles {
+    fn run(
+        &self,
+        _aft: Option<&Self::Aft<'e>>,
+        st: &mut P::St<'e>,
+        th: &mut impl Th<'e, P>,
+    ) -> Result<Self::Aft<'e>, Suspend> {
+        let _st = Self::unwrap_st(st.like_mut());
+
+        let project = th.demand(GetProject {
+            directory: self.directory.clone(),
+        })?;
+
+        let Some(project) = project.value.clone() else {
+            return Ok(WithErrors::new(Arc::from([]), project.errors.clone()));
+        };
+
+        let main = self.directory.join(&project.main);
+
+        let Some(main) = th.canonicalize(&main) else {
+            return Ok(WithErrors::new_one(
+                Arc::from([]),
+                Error::FailedToCanonicalize(main.into()),
+            ));
+        };
+
+        // Every file reachable from the main file through imports is part of the package
+        let mut files = Vec::new();
+        let mut errors = Vec::new();
+        let mut visited = HashSet::new();
+        let mut pending = vec![Arc::new(main)];
+
+        while let Some(filename) = pending.pop() {
+            if !visited.insert(filename.clone()) {
+                continue;
+            }
+
+            let imports = th.demand(Imports {
+                filename: filename.clone(),
+            })?;
+            errors.extend(imports.errors.iter_unordered().cloned());
+            pending.extend(imports.value.iter().rev().cloned());
+            files.push(filename);
+        }
+
+        Ok(WithErrors::new(files.into(), errors.into_iter().collect()))
+    }
+}
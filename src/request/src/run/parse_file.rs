@@ -1,6 +1,7 @@
-use crate::{Error, Like, ParseFile, Pf, Run, Suspend, Th, UnwrapSt, WithErrors};
+use crate::{Error, Like, ParseFile, Pf, Run, SourceLocation, Suspend, Th, UnwrapSt, WithErrors};
 use by_address::ByAddress;
 use document::Document;
+use syntax_tree::{BareSyntaxKind, IdentifierWarningKind};
 
 impl<'e, P: Pf> Run<'e, P> for ParseFile {
     fn run(
@@ -23,6 +24,34 @@ impl<'e, P: Pf> Run<'e, P> for ParseFile {
         let document = Document::new(content);
         let syntax_tree = parser_adept::reparse(&document, None, document.full_range());
         // let _ = syntax_tree.dump(&mut std::io::stdout(), 0);
-        Ok(WithErrors::no_errors(Some(ByAddress(syntax_tree))))
+
+        let mut errors = syntax_tree
+            .descendants()
+            .filter_map(|node| match node.bare().kind() {
+                BareSyntaxKind::Error { description } => Some(
+                    Error::Syntax(description.as_str().into()).at(SourceLocation::new(
+                        self.filename.clone(),
+                        node.text_range(),
+                    )),
+                ),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        for warning in syntax_tree.identifier_warnings() {
+            let error = match warning.kind {
+                IdentifierWarningKind::MixedScript => Error::MixedScriptIdentifier(warning.name),
+                IdentifierWarningKind::Confusable(other) => {
+                    Error::ConfusableIdentifier(warning.name, other)
+                }
+            };
+
+            errors.push(error.at(SourceLocation::new(self.filename.clone(), warning.range)));
+        }
+
+        Ok(WithErrors::new(
+            Some(ByAddress(syntax_tree)),
+            errors.into_iter().collect(),
+        ))
     }
 }
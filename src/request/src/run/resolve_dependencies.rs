@@ -0,0 +1,100 @@
+use crate::{
+    Error, GetProject, Like, Package, Pf, ResolveDependencies, Run, Suspend, Th, UnwrapSt,
+    WithErrors,
+};
+use std::{collections::HashSet, path::PathBuf, sync::Arc};
+use vfs::Canonical;
+
+impl<'e, P: Pf> Run<'e, P> for ResolveDependencies {
+    fn run(
+        &self,
+        _aft: Option<&Self::Aft<'e>>,
+        st: &mut P::St<'e>,
+        th: &mut impl Th<'e, P>,
+    ) -> Result<Self::Aft<'e>, Suspend> {
+        let _st = Self::unwrap_st(st.like_mut());
+
+        let mut resolver = Resolver::default();
+        let label = self
+            .directory
+            .file_name()
+            .map(|name| name.to_string_lossy().into())
+            .unwrap_or_else(|| "<root>".into());
+
+        resolver.visit(th, self.directory.clone(), label)?;
+
+        Ok(WithErrors::new(
+            resolver.packages.into(),
+            resolver.errors.into_iter().collect(),
+        ))
+    }
+}
+
+#[derive(Default)]
+struct Resolver {
+    packages: Vec<Package>,
+    errors: Vec<Error>,
+    finished: HashSet<Arc<Canonical<PathBuf>>>,
+    stack: Vec<(Arc<Canonical<PathBuf>>, Arc<str>)>,
+}
+
+impl Resolver {
+    // Packages are added in dependency order, so the root package is always last
+    fn visit<'e, P: Pf>(
+        &mut self,
+        th: &mut impl Th<'e, P>,
+        directory: Arc<Canonical<PathBuf>>,
+        label: Arc<str>,
+    ) -> Result<(), Suspend> {
+        if self.finished.contains(&directory) {
+            return Ok(());
+        }
+
+        if let Some(start) = self.stack.iter().position(|(dir, _)| *dir == directory) {
+            let chain = self.stack[start..]
+                .iter()
+                .map(|(_, label)| label.as_ref())
+                .chain(std::iter::once(label.as_ref()))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+
+            self.errors.push(Error::CyclicDependency(chain.into()));
+            return Ok(());
+        }
+
+        let project = th.demand(GetProject {
+            directory: directory.clone(),
+        })?;
+        self.errors.extend(project.errors.iter_unordered().cloned());
+
+        let Some(project) = project.value.clone() else {
+            return Ok(());
+        };
+
+        self.stack.push((directory.clone(), label));
+        let mut dependencies = Vec::new();
+
+        for dependency in project.dependencies.iter() {
+            let Some(dependency_directory) = th.canonicalize(&directory.join(&dependency.path))
+            else {
+                self.errors
+                    .push(Error::FailedToResolveDependency(dependency.name.clone()));
+                continue;
+            };
+
+            let dependency_directory = Arc::new(dependency_directory);
+            dependencies.push((dependency.name.clone(), dependency_directory.clone()));
+            self.visit(th, dependency_directory, dependency.name.clone())?;
+        }
+
+        self.stack.pop();
+        self.finished.insert(directory.clone());
+
+        self.packages.push(Package {
+            directory,
+            project,
+            dependencies: dependencies.into(),
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use crate::{Like, PackageFiles, ParseFile, Pf, ResolvePackageSymbol, Run, Suspend, Th, UnwrapSt};
+use syntax_tree::ast::{self, AstNode};
+
+impl<'e, P: Pf> Run<'e, P> for ResolvePackageSymbol {
+    fn run(
+        &self,
+        _aft: Option<&Self::Aft<'e>>,
+        st: &mut P::St<'e>,
+        th: &mut impl Th<'e, P>,
+    ) -> Result<Self::Aft<'e>, Suspend> {
+        let _st = Self::unwrap_st(st.like_mut());
+
+        let files = th
+            .demand(PackageFiles {
+                directory: self.directory.clone(),
+            })?
+            .value
+            .clone();
+
+        for filename in files.iter() {
+            let parsed = th.demand(ParseFile {
+                filename: filename.clone(),
+            })?;
+
+            let defines = parsed
+                .value
+                .iter()
+                .flat_map(|parsed| ast::Root::cast(parsed.0.clone()))
+                .flat_map(|root| root.bindings())
+                .any(|binding| {
+                    binding.name().and_then(|name| name.text()).as_deref()
+                        == Some(self.name.as_ref())
+                });
+
+            if defines {
+                return Ok(Some(filename.clone()));
+            }
+        }
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{Imports, Like, ParseFile, Pf, ResolveSymbol, Run, Suspend, Th, UnwrapSt};
+use std::{path::PathBuf, sync::Arc};
+use syntax_tree::ast::{self, AstNode};
+use vfs::Canonical;
+
+impl<'e, P: Pf> Run<'e, P> for ResolveSymbol {
+    fn run(
+        &self,
+        _aft: Option<&Self::Aft<'e>>,
+        st: &mut P::St<'e>,
+        th: &mut impl Th<'e, P>,
+    ) -> Result<Self::Aft<'e>, Suspend> {
+        let _st = Self::unwrap_st(st.like_mut());
+
+        // Bindings in the same file take priority over imported ones
+        if defines(th, &self.filename, &self.name)? {
+            return Ok(Some(self.filename.clone()));
+        }
+
+        let imports = th
+            .demand(Imports {
+                filename: self.filename.clone(),
+            })?
+            .value
+            .clone();
+
+        // Imports aren't re-exported, so only the bindings of files that are imported directly
+        // are visible, the same as what the editor completes
+        for filename in imports.iter() {
+            if defines(th, filename, &self.name)? {
+                return Ok(Some(filename.clone()));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+fn defines<'e, P: Pf>(
+    th: &mut impl Th<'e, P>,
+    filename: &Arc<Canonical<PathBuf>>,
+    name: &str,
+) -> Result<bool, Suspend> {
+    let parsed = th.demand(ParseFile {
+        filename: filename.clone(),
+    })?;
+
+    Ok(parsed
+        .value
+        .iter()
+        .flat_map(|parsed| ast::Root::cast(parsed.0.clone()))
+        .flat_map(|root| root.bindings())
+        .any(|binding| binding.name().and_then(|name| name.text()).as_deref() == Some(name)))
+}
@@ -26,6 +26,18 @@ impl TopErrors {
     }
 }
 
+impl FromIterator<Error> for TopErrors {
+    fn from_iter<T: IntoIterator<Item = Error>>(errors: T) -> Self {
+        let node = TopErrorsNode::new(errors);
+
+        if node.errors.is_empty() {
+            Self::default()
+        } else {
+            Self::from(node)
+        }
+    }
+}
+
 impl From<TopErrorsNode> for TopErrors {
     fn from(value: TopErrorsNode) -> Self {
         Self {
@@ -0,0 +1,183 @@
+#![cfg(test)]
+
+use crate::{ConfigValue, Error, check_fields, field_index};
+use document::Document;
+use std::sync::Arc;
+use syntax_tree::{
+    SyntaxNode,
+    ast::{self, AstNode},
+};
+
+fn parse(source: &str) -> Arc<SyntaxNode> {
+    let document = Document::new(source);
+    parser_adept::reparse(&document, None, document.full_range())
+}
+
+fn field_errors(source: &str) -> Vec<Error> {
+    check_fields(&parse(source))
+        .into_iter()
+        .map(|(error, _)| error)
+        .collect()
+}
+
+/// The resolved index of each field access in `source`, in source order
+fn field_indices(source: &str) -> Vec<Option<Result<usize, Error>>> {
+    let mut accesses = parse(source)
+        .descendants()
+        .flat_map(ast::FieldAccess::cast)
+        .collect::<Vec<_>>();
+
+    accesses.sort_by_key(|access| access.syntax().text_range().start);
+    accesses.iter().map(field_index).collect()
+}
+
+#[test]
+fn field_index_of_record_value() {
+    assert_eq!(field_indices("a :: @record(x: 1, y: 2).y\n"), [Some(Ok(1))]);
+}
+
+#[test]
+fn field_index_through_bindings() {
+    let source = "point :: @record(x: 1, y: 2)\nalias :: point\ny :: alias.y\nx :: (point).x\n";
+    assert_eq!(field_indices(source), [Some(Ok(1)), Some(Ok(0))]);
+}
+
+#[test]
+fn field_index_of_typed_param() {
+    let source = "f :: @fn(p: @Record { x: Nat, y: Nat }): Nat { p.y }\n";
+    assert_eq!(field_indices(source), [Some(Ok(1))]);
+}
+
+#[test]
+fn field_index_of_let() {
+    let source = "f :: @fn(): Nat {\n    p := @record(x: 1, y: 2)\n    p.x\n}\n";
+    assert_eq!(field_indices(source), [Some(Ok(0))]);
+}
+
+#[test]
+fn field_index_is_unknown_without_types() {
+    let source = "f :: @fn(p) { p.x }\ng :: h.x\nloop :: loop.x\n";
+    assert_eq!(field_indices(source), [None, None, None]);
+}
+
+#[test]
+fn unknown_field() {
+    assert_eq!(
+        field_errors("p :: @record(x: 1)\ny :: p.y\nz :: @record(1, 2).x\n"),
+        [
+            Error::UnknownField("y".into()),
+            Error::UnknownField("x".into()),
+        ]
+    );
+}
+
+#[test]
+fn duplicate_field() {
+    assert_eq!(
+        field_errors("p :: @record(x: 1, x: 2)\nT :: @Record { y: Nat, y: Bool }\n"),
+        [
+            Error::DuplicateField("x".into()),
+            Error::DuplicateField("y".into()),
+        ]
+    );
+}
+
+#[test]
+fn mixed_positional_and_named_fields() {
+    assert_eq!(
+        field_errors("p :: @record(1, y: 2)\n"),
+        [Error::MixedRecordFields]
+    );
+}
+
+fn config_string(value: &str) -> ConfigValue {
+    ConfigValue::String(value.into())
+}
+
+#[test]
+fn config_comments() {
+    let content = r#"
+        // The name of the package
+        {
+            name: "app", // trailing comment
+            // main: "ignored.adept",
+            version: 1
+        }
+        // after everything
+    "#;
+
+    assert_eq!(
+        ConfigValue::parse(content),
+        Ok(ConfigValue::Object(vec![
+            ("name".into(), config_string("app")),
+            ("version".into(), ConfigValue::Integer(1)),
+        ]))
+    );
+}
+
+#[test]
+fn config_trailing_commas() {
+    assert_eq!(
+        ConfigValue::parse(r#"{ "quoted": [1_000, true, false,], bare: {}, }"#),
+        Ok(ConfigValue::Object(vec![
+            (
+                "quoted".into(),
+                ConfigValue::Array(vec![
+                    ConfigValue::Integer(1000),
+                    ConfigValue::Bool(true),
+                    ConfigValue::Bool(false),
+                ])
+            ),
+            ("bare".into(), ConfigValue::Object(vec![])),
+        ]))
+    );
+}
+
+#[test]
+fn config_string_escapes() {
+    assert_eq!(
+        ConfigValue::parse(r#""a\"b\\c\/d\ne\tf""#),
+        Ok(config_string("a\"b\\c/d\ne\tf"))
+    );
+}
+
+#[test]
+fn config_errors() {
+    assert_eq!(
+        ConfigValue::parse(r#"{ name "app" }"#),
+        Err(Error::ExpectedChar(':'))
+    );
+    assert_eq!(
+        ConfigValue::parse(r#"{ name: "app" "#),
+        Err(Error::ExpectedChar('}'))
+    );
+    assert_eq!(ConfigValue::parse("[1 2]"), Err(Error::ExpectedChar(']')));
+    assert_eq!(
+        ConfigValue::parse(r#"{ name: "app }"#),
+        Err(Error::ExpectedChar('"'))
+    );
+    assert_eq!(
+        ConfigValue::parse(r#"{ name: "\q" }"#),
+        Err(Error::InvalidProjectConfigSyntax)
+    );
+    assert_eq!(
+        ConfigValue::parse("{ enabled: yes }"),
+        Err(Error::InvalidProjectConfigSyntax)
+    );
+    assert_eq!(
+        ConfigValue::parse("{ a: 1,, }"),
+        Err(Error::InvalidProjectConfigSyntax)
+    );
+    assert_eq!(
+        ConfigValue::parse("{} {}"),
+        Err(Error::InvalidProjectConfigSyntax)
+    );
+    assert_eq!(
+        ConfigValue::parse("99999999999999999999999"),
+        Err(Error::InvalidProjectConfigSyntax)
+    );
+    assert_eq!(
+        ConfigValue::parse(""),
+        Err(Error::InvalidProjectConfigSyntax)
+    );
+}
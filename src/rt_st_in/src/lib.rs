@@ -3,7 +3,7 @@ mod react;
 mod req_cache;
 mod wake_dependants;
 
-use crate::query::QueryFiles;
+use crate::query::QueryInputs;
 use connection::Connection;
 pub use query::RtStInQuery;
 use react::*;
@@ -12,7 +12,7 @@ use request::{
     BlockOn, Major, Pf, QueryMode, QueryThen, Rt, ShouldUnblock, TaskStatus, TaskStatusKind,
     TopErrorsNode, rt_trace,
 };
-use std::collections::HashMap;
+use std::{collections::HashMap, path::PathBuf};
 pub use wake_dependants::*;
 
 pub struct RtStIn<'e, P: Pf>
@@ -21,6 +21,7 @@ where
 {
     pub(crate) cache: ReqCache<'e, P>,
     pub(crate) current: P::Rev,
+    pub(crate) inputs: HashMap<P::Req<'e>, Vec<PathBuf>>,
     pub cache_to_disk: bool,
 }
 
@@ -29,9 +30,14 @@ where
     P::Rev: Major,
 {
     pub fn new(cache: ReqCache<'e, P>) -> Self {
+        // Continue after any revisions restored from disk, so that
+        // the restored results get verified again before being used
+        let current = cache.latest_rev().unwrap_or_default();
+
         Self {
             cache,
-            current: P::Rev::default(),
+            current,
+            inputs: HashMap::new(),
             cache_to_disk: false,
         }
     }
@@ -39,6 +45,11 @@ where
     pub fn cache(&self) -> &ReqCache<'e, P> {
         &self.cache
     }
+
+    /// The paths that the most recently completed query for `req` depended on
+    pub fn inputs(&self, req: &P::Req<'e>) -> Option<&[PathBuf]> {
+        self.inputs.get(req).map(Vec::as_slice)
+    }
 }
 
 impl<'e, P: Pf> Rt<'e, P> for RtStIn<'e, P>
@@ -64,7 +75,7 @@ where
             waiting: HashMap::new(),
             rev: self.current,
             req,
-            files: QueryFiles::default(),
+            inputs: QueryInputs::default(),
             then,
             connection,
         }
@@ -87,6 +98,11 @@ where
             }
         }
 
+        let mut inputs = query.inputs.paths().map(PathBuf::from).collect::<Vec<_>>();
+        inputs.sort();
+        inputs.dedup();
+        self.inputs.insert(query.req.clone(), inputs);
+
         let status = self.cache.get(&query.req);
 
         match status {
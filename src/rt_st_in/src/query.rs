@@ -1,15 +1,55 @@
 use crate::{Pf, QueryThen};
 use connection::Connection;
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use vfs::Canonical;
 
 pub struct RtStInQuery<'e, P: Pf> {
     pub(crate) queue: Vec<P::Req<'e>>,
     pub(crate) waiting: HashMap<P::Req<'e>, Vec<P::Req<'e>>>,
     pub(crate) rev: P::Rev,
     pub(crate) req: P::Req<'e>,
-    pub(crate) files: QueryFiles,
+    pub(crate) inputs: QueryInputs,
     pub then: QueryThen<'e, P>,
     pub connection: Connection,
 }
 
-pub type QueryFiles = HashMap<PathBuf, Arc<str>>;
+/// What the requests of a query have seen of the file system,
+/// which then stays the same for the rest of the query
+#[derive(Default)]
+pub struct QueryInputs {
+    pub files: HashMap<PathBuf, Arc<str>>,
+    pub paths: HashMap<PathBuf, PathProbe>,
+}
+
+impl QueryInputs {
+    /// Every path that was read or probed, including ones that didn't exist
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files
+            .keys()
+            .chain(self.paths.keys())
+            .map(PathBuf::as_path)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PathProbe {
+    pub canonical: Option<Canonical<PathBuf>>,
+    pub is_file: bool,
+    pub is_dir: bool,
+}
+
+impl PathProbe {
+    pub fn new(path: &Path) -> Self {
+        let metadata = std::fs::metadata(path).ok();
+
+        Self {
+            canonical: Canonical::new(path).ok(),
+            is_file: metadata.as_ref().is_some_and(|metadata| metadata.is_file()),
+            is_dir: metadata.as_ref().is_some_and(|metadata| metadata.is_dir()),
+        }
+    }
+}
@@ -1,9 +1,18 @@
-use crate::{RtStIn, RtStInQuery, query::QueryFiles, wake_dependants};
+use crate::{
+    RtStIn, RtStInQuery,
+    query::{PathProbe, QueryInputs},
+    wake_dependants,
+};
 use request::{
     Completed, IsImpure, Like, Major, Pf, Req, Restarting, RunDispatch, Running, Suspend, Task,
     TaskStatus, TaskStatusKind, Th, UnLike, UnwrapAft, rt_trace,
 };
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use vfs::Canonical;
 
 pub fn react<'e, P: Pf>(rt: &mut RtStIn<'e, P>, query: &mut RtStInQuery<'e, P>, req: P::Req<'e>)
 where
@@ -209,18 +218,18 @@ where
 {
     rt: &'rt RtStIn<'e, P>,
     suspend_on: HashSet<P::Req<'e>>,
-    files: &'q mut QueryFiles,
+    inputs: &'q mut QueryInputs,
 }
 
 impl<'rt, 'e, 'q, P: Pf> ThStIn<'rt, 'e, 'q, P>
 where
     P::Rev: Major,
 {
-    pub fn new(rt: &'rt RtStIn<'e, P>, files: &'q mut QueryFiles) -> Self {
+    pub fn new(rt: &'rt RtStIn<'e, P>, inputs: &'q mut QueryInputs) -> Self {
         Self {
             rt,
             suspend_on: HashSet::with_capacity(16),
-            files,
+            inputs,
         }
     }
 }
@@ -264,13 +273,37 @@ where
     }
 
     fn read_file<'th, 'a>(&'th mut self, filename: &'a std::path::Path) -> Result<Arc<str>, ()> {
-        if let Some(content) = self.files.get(filename) {
+        if let Some(content) = self.inputs.files.get(filename) {
             return Ok(Arc::clone(content));
         }
 
         let content = std::fs::read_to_string(filename).map_err(|_| ())?.into();
-        self.files.insert(filename.into(), content);
-        self.files.get(filename).map(Arc::clone).ok_or(())
+        self.inputs.files.insert(filename.into(), content);
+        self.inputs.files.get(filename).map(Arc::clone).ok_or(())
+    }
+
+    fn is_file(&mut self, path: &Path) -> bool {
+        self.probe(path).is_file
+    }
+
+    fn is_dir(&mut self, path: &Path) -> bool {
+        self.probe(path).is_dir
+    }
+
+    fn canonicalize(&mut self, path: &Path) -> Option<Canonical<PathBuf>> {
+        self.probe(path).canonical.clone()
+    }
+}
+
+impl<'rt, 'e, 'q, P: Pf> ThStIn<'rt, 'e, 'q, P>
+where
+    P::Rev: Major,
+{
+    fn probe(&mut self, path: &Path) -> &PathProbe {
+        self.inputs
+            .paths
+            .entry(path.into())
+            .or_insert_with(|| PathProbe::new(path))
     }
 }
 
@@ -287,7 +320,7 @@ where
     rt_trace!("Processing {:?}, queue: {:?}", req, &query.queue);
 
     let st = &mut running.st;
-    let mut th = ThStIn::new(rt, &mut query.files);
+    let mut th = ThStIn::new(rt, &mut query.inputs);
     let result = req.run_dispath(running.prev_aft.as_ref().map(Like::like_ref), st, &mut th);
 
     // Remove existing dependencies from set of new requested dependencies
@@ -43,7 +43,37 @@ impl<'e, P: Pf> ReqCache<'e, P> {
         let mut de = serde_json::Deserializer::new(io_read);
         let value = <Self as serde::de::Deserialize>::deserialize(&mut de).map_err(|_| ())?;
         de.end().map_err(|_| ())?;
-        Ok(value)
+        Ok(value.without_unverifiable())
+    }
+
+    /// Removes restored entries that depend on requests which were never
+    /// persisted, since they can't be verified again
+    fn without_unverifiable(mut self) -> Self {
+        loop {
+            let unverifiable = self
+                .kv
+                .inner
+                .iter()
+                .filter(|(_, status)| {
+                    status.as_ref().is_none_or(|status| {
+                        status
+                            .task
+                            .requested
+                            .iter()
+                            .any(|dep| !self.kv.inner.contains_key(dep))
+                    })
+                })
+                .map(|(req, _)| req.clone())
+                .collect::<Vec<_>>();
+
+            if unverifiable.is_empty() {
+                return self;
+            }
+
+            for req in unverifiable {
+                self.kv.inner.remove(&req);
+            }
+        }
     }
 
     pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ()> {
@@ -60,6 +90,20 @@ impl<'e, P: Pf> ReqCache<'e, P> {
         Ok(())
     }
 
+    /// The most recent revision that any cached request was verified at
+    pub fn latest_rev(&self) -> Option<P::Rev> {
+        self.kv
+            .inner
+            .values()
+            .flatten()
+            .map(|status| status.task.verified_at)
+            .max()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &P::Req<'e>> {
+        self.kv.inner.keys()
+    }
+
     pub fn get(&self, key: &P::Req<'e>) -> Option<&Option<TaskStatus<'e, P>>> {
         self.kv.inner.get(key)
     }
@@ -0,0 +1,728 @@
+//! Typed views over the syntax tree.
+//!
+//! Each struct wraps a `SyntaxNode` of a single syntactic construct and exposes its parts
+//! through accessor methods, so consumers don't have to walk children by `BareSyntaxKind`.
+//! Accessors return `None` for parts that are missing because of parse errors.
+
+use crate::{BareSyntaxKind, BuiltinType, SyntaxNode};
+use derive_more::From;
+use num_bigint::BigInt;
+use std::sync::Arc;
+use token::Punct;
+
+pub trait AstNode: Sized {
+    fn can_cast(kind: &BareSyntaxKind) -> bool;
+
+    fn cast(node: Arc<SyntaxNode>) -> Option<Self>;
+
+    fn syntax(&self) -> &Arc<SyntaxNode>;
+}
+
+/// Generates a struct for each construct, which can be cast from nodes matching its pattern
+macro_rules! ast_nodes {
+    ($($(#[$meta:meta])* $name:ident: $pattern:pat,)*) => {
+        $(
+            $(#[$meta])*
+            #[derive(Clone, Debug)]
+            pub struct $name(Arc<SyntaxNode>);
+
+            impl AstNode for $name {
+                fn can_cast(kind: &BareSyntaxKind) -> bool {
+                    matches!(kind, $pattern)
+                }
+
+                fn cast(node: Arc<SyntaxNode>) -> Option<Self> {
+                    Self::can_cast(node.bare().kind()).then(|| Self(node))
+                }
+
+                fn syntax(&self) -> &Arc<SyntaxNode> {
+                    &self.0
+                }
+            }
+        )*
+    };
+}
+
+/// Generates an enum over several constructs, which is cast to the first matching variant
+macro_rules! ast_enum {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident($node:ident),)* }) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug)]
+        pub enum $name {
+            $($variant($node),)*
+        }
+
+        impl AstNode for $name {
+            fn can_cast(kind: &BareSyntaxKind) -> bool {
+                $($node::can_cast(kind))||*
+            }
+
+            fn cast(node: Arc<SyntaxNode>) -> Option<Self> {
+                let kind = node.bare().kind();
+                $(
+                    if $node::can_cast(kind) {
+                        return Some(Self::$variant($node(node)));
+                    }
+                )*
+                None
+            }
+
+            fn syntax(&self) -> &Arc<SyntaxNode> {
+                match self {
+                    $(Self::$variant(node) => node.syntax(),)*
+                }
+            }
+        }
+    };
+}
+
+ast_nodes! {
+    Root: BareSyntaxKind::Root,
+    Binding: BareSyntaxKind::Binding,
+    Import: BareSyntaxKind::Import,
+    Eval: BareSyntaxKind::Eval,
+    Name: BareSyntaxKind::Name | BareSyntaxKind::ImplicitName,
+    Term: BareSyntaxKind::Term,
+    TypeAnnotation: BareSyntaxKind::TypeAnnotation,
+    Block: BareSyntaxKind::Block,
+    ParenthesizedTerm: BareSyntaxKind::ParenthesizedTerm,
+    Variable: BareSyntaxKind::Variable(_),
+    QualifiedVariable: BareSyntaxKind::QualifiedVariable { .. },
+    /// `true`, `false`, `void`, or a number, string or character literal
+    Literal: BareSyntaxKind::TrueValue
+        | BareSyntaxKind::FalseValue
+        | BareSyntaxKind::VoidValue
+        | BareSyntaxKind::Integer(_)
+        | BareSyntaxKind::Float(_)
+        | BareSyntaxKind::String(_)
+        | BareSyntaxKind::CString(_)
+        | BareSyntaxKind::ByteString(_)
+        | BareSyntaxKind::Character(_),
+    /// A type without parameters, like `Bool` or `Nat`
+    PrimitiveType: BareSyntaxKind::BuiltinType(
+        BuiltinType::Bool | BuiltinType::Void | BuiltinType::Type | BuiltinType::Nat
+    ),
+    FnValue: BareSyntaxKind::FnValue,
+    FnType: BareSyntaxKind::BuiltinType(BuiltinType::Fn),
+    ParamList: BareSyntaxKind::ParamList,
+    Param: BareSyntaxKind::Param,
+    ParamHead: BareSyntaxKind::ParamHead,
+    ArgList: BareSyntaxKind::ArgList(_),
+    Call: BareSyntaxKind::Call,
+    Let: BareSyntaxKind::Let,
+    Nth: BareSyntaxKind::Nth,
+    FieldAccess: BareSyntaxKind::FieldAccess,
+    BinaryOp: BareSyntaxKind::BinaryOp,
+    UnaryOp: BareSyntaxKind::UnaryOp,
+    IfValue: BareSyntaxKind::IfValue,
+    /// The `then` or `else` branch of an `@if`, which is a term or a block
+    IfBranch: BareSyntaxKind::IfThen | BareSyntaxKind::IfElse,
+    /// `@bool_elim`, `@nat_elim` or `@nat_succ`
+    Intrinsic: BareSyntaxKind::BoolElim | BareSyntaxKind::NatElim | BareSyntaxKind::NatSucc,
+    Match: BareSyntaxKind::Match,
+    MatchMotive: BareSyntaxKind::MatchMotive,
+    MatchArm: BareSyntaxKind::MatchArm,
+    Pattern: BareSyntaxKind::Pattern,
+    RecordType: BareSyntaxKind::BuiltinType(BuiltinType::Record),
+    FieldDef: BareSyntaxKind::FieldDef,
+    RecordValue: BareSyntaxKind::RecordValue,
+    FieldInit: BareSyntaxKind::FieldInit,
+    VariantType: BareSyntaxKind::BuiltinType(BuiltinType::Variant),
+    VariantDef: BareSyntaxKind::VariantDef,
+    VariantValue: BareSyntaxKind::VariantValue,
+}
+
+ast_enum! {
+    /// The construct that a `Term` consists of
+    Expr {
+        Literal(Literal),
+        PrimitiveType(PrimitiveType),
+        Variable(Variable),
+        QualifiedVariable(QualifiedVariable),
+        Parenthesized(ParenthesizedTerm),
+        Block(Block),
+        Fn(FnValue),
+        FnType(FnType),
+        Call(Call),
+        Let(Let),
+        Nth(Nth),
+        FieldAccess(FieldAccess),
+        BinaryOp(BinaryOp),
+        UnaryOp(UnaryOp),
+        If(IfValue),
+        Intrinsic(Intrinsic),
+        Match(Match),
+        RecordType(RecordType),
+        Record(RecordValue),
+        VariantType(VariantType),
+        Variant(VariantValue),
+        Eval(Eval),
+    }
+}
+
+fn child<N: AstNode>(node: &Arc<SyntaxNode>) -> Option<N> {
+    node.children().find_map(N::cast)
+}
+
+fn children<N: AstNode>(node: &Arc<SyntaxNode>) -> impl Iterator<Item = N> + use<N> {
+    node.children()
+        .filter_map(N::cast)
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+fn nth_child<N: AstNode>(node: &Arc<SyntaxNode>, n: usize) -> Option<N> {
+    children(node).nth(n)
+}
+
+fn is_punct(node: &Arc<SyntaxNode>, puncts: &[&'static str]) -> bool {
+    matches!(node.bare().kind(), BareSyntaxKind::Punct(punct) if puncts.iter().any(|expected| *punct == Punct::new(expected)))
+}
+
+/// The children after the first punct that is one of `puncts`
+fn after_punct(node: &Arc<SyntaxNode>, puncts: &[&'static str]) -> Vec<Arc<SyntaxNode>> {
+    node.children()
+        .skip_while(|child| !is_punct(child, puncts))
+        .skip(1)
+        .collect()
+}
+
+fn punct(node: &Arc<SyntaxNode>) -> Option<Punct> {
+    node.children().find_map(|child| match child.bare().kind() {
+        BareSyntaxKind::Punct(punct) => Some(*punct),
+        _ => None,
+    })
+}
+
+impl Root {
+    pub fn bindings(&self) -> impl Iterator<Item = Binding> + use<> {
+        children(&self.0)
+    }
+
+    pub fn imports(&self) -> impl Iterator<Item = Import> + use<> {
+        children(&self.0)
+    }
+
+    pub fn evals(&self) -> impl Iterator<Item = Eval> + use<> {
+        children(&self.0)
+    }
+}
+
+impl Binding {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.0)
+    }
+
+    pub fn value(&self) -> Option<Term> {
+        child(&self.0)
+    }
+
+    pub fn doc(&self) -> Option<String> {
+        self.0.bare().doc_comment()
+    }
+}
+
+impl Import {
+    pub fn path(&self) -> Option<Arc<str>> {
+        self.0.find_string()
+    }
+}
+
+impl Eval {
+    pub fn value(&self) -> Option<Term> {
+        child(&self.0)
+    }
+}
+
+impl Name {
+    pub fn text(&self) -> Option<Arc<str>> {
+        self.0
+            .children()
+            .find_map(|child| match child.bare().kind() {
+                BareSyntaxKind::Identifier(name) => Some(name.clone()),
+                _ => None,
+            })
+    }
+}
+
+impl Term {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl TypeAnnotation {
+    pub fn ty(&self) -> Option<Term> {
+        child(&self.0)
+    }
+}
+
+impl Block {
+    pub fn terms(&self) -> impl Iterator<Item = Term> + use<> {
+        children(&self.0)
+    }
+}
+
+impl ParenthesizedTerm {
+    pub fn term(&self) -> Option<Term> {
+        child(&self.0)
+    }
+}
+
+impl Variable {
+    pub fn name(&self) -> Arc<str> {
+        match self.0.bare().kind() {
+            BareSyntaxKind::Variable(name) => name.clone(),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl QualifiedVariable {
+    pub fn namespace(&self) -> Arc<str> {
+        match self.0.bare().kind() {
+            BareSyntaxKind::QualifiedVariable { namespace, .. } => namespace.clone(),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn name(&self) -> Option<Arc<str>> {
+        match self.0.bare().kind() {
+            BareSyntaxKind::QualifiedVariable { name, .. } => name.clone(),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Literal {
+    pub fn kind(&self) -> &BareSyntaxKind {
+        self.0.bare().kind()
+    }
+
+    pub fn integer(&self) -> Option<Arc<BigInt>> {
+        match self.kind() {
+            BareSyntaxKind::Integer(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl PrimitiveType {
+    pub fn builtin_type(&self) -> &BuiltinType {
+        match self.0.bare().kind() {
+            BareSyntaxKind::BuiltinType(builtin_type) => builtin_type,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl FnValue {
+    pub fn param_list(&self) -> Option<ParamList> {
+        child(&self.0)
+    }
+
+    pub fn return_type(&self) -> Option<TypeAnnotation> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Option<Block> {
+        child(&self.0)
+    }
+}
+
+impl FnType {
+    pub fn param_list(&self) -> Option<ParamList> {
+        child(&self.0)
+    }
+
+    pub fn return_type(&self) -> Option<TypeAnnotation> {
+        child(&self.0)
+    }
+}
+
+impl ParamList {
+    pub fn params(&self) -> impl Iterator<Item = Param> + use<> {
+        children(&self.0)
+    }
+}
+
+impl Param {
+    pub fn heads(&self) -> impl Iterator<Item = ParamHead> + use<> {
+        children(&self.0)
+    }
+
+    pub fn type_annotation(&self) -> Option<TypeAnnotation> {
+        child(&self.0)
+    }
+}
+
+impl ParamHead {
+    pub fn name(&self) -> Option<Name> {
+        self.0.find(BareSyntaxKind::Name).and_then(Name::cast)
+    }
+
+    /// The name of an implicit parameter, like the `T` of `$T`
+    pub fn implicit_name(&self) -> Option<Name> {
+        self.0
+            .find(BareSyntaxKind::ImplicitName)
+            .and_then(Name::cast)
+    }
+
+    /// The name that the parameter binds, which is the implicit name when there is no other
+    pub fn binder(&self) -> Option<Arc<str>> {
+        self.name()
+            .or_else(|| self.implicit_name())
+            .and_then(|name| name.text())
+    }
+
+    pub fn implicitness(&self) -> NamedImplicitness {
+        match (
+            self.name(),
+            self.implicit_name().and_then(|name| name.text()),
+        ) {
+            (Some(_), Some(implicit_name)) => NamedImplicitness::ImplicitWithName(implicit_name),
+            (None, Some(_)) => Implicitness::Implicit.into(),
+            (_, None) => Implicitness::Explicit.into(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Implicitness {
+    Explicit,
+    Implicit,
+}
+
+#[derive(Clone, Debug, From)]
+pub enum NamedImplicitness {
+    ImplicitWithName(Arc<str>),
+    Implicitness(Implicitness),
+}
+
+impl NamedImplicitness {
+    pub fn matches_param(&self, param_name: &str, param_implicitness: Implicitness) -> bool {
+        match self {
+            NamedImplicitness::ImplicitWithName(name) => {
+                param_name == name.as_ref() && param_implicitness == Implicitness::Implicit
+            }
+            NamedImplicitness::Implicitness(lambda_implicitness) => {
+                param_implicitness == *lambda_implicitness
+            }
+        }
+    }
+
+    pub fn to_implicitness(&self) -> Implicitness {
+        match self {
+            NamedImplicitness::ImplicitWithName(_) => Implicitness::Implicit,
+            NamedImplicitness::Implicitness(implicitness) => *implicitness,
+        }
+    }
+}
+
+impl ArgList {
+    pub fn args(&self) -> impl Iterator<Item = Term> + use<> {
+        children(&self.0)
+    }
+}
+
+impl Call {
+    pub fn callee(&self) -> Option<Term> {
+        child(&self.0)
+    }
+
+    pub fn arg_list(&self) -> Option<ArgList> {
+        child(&self.0)
+    }
+}
+
+impl Let {
+    pub fn binder(&self) -> Option<Arc<str>> {
+        child::<Term>(&self.0).and_then(|term| term.0.find_var())
+    }
+
+    /// The type of the binder, like the `Nat` of `x: Nat = 1`
+    pub fn ty(&self) -> Option<Term> {
+        after_punct(&self.0, &[":"])
+            .into_iter()
+            .take_while(|child| !is_punct(child, &["="]))
+            .find_map(Term::cast)
+    }
+
+    pub fn value(&self) -> Option<Term> {
+        after_punct(&self.0, &["=", ":="])
+            .into_iter()
+            .find_map(Term::cast)
+    }
+
+    pub fn body(&self) -> Option<Term> {
+        after_punct(&self.0, &["=", ":="])
+            .into_iter()
+            .filter_map(Term::cast)
+            .nth(1)
+    }
+}
+
+impl Nth {
+    pub fn record(&self) -> Option<Term> {
+        child(&self.0)
+    }
+
+    pub fn index(&self) -> Option<Arc<BigInt>> {
+        self.0.find_integer()
+    }
+}
+
+impl FieldAccess {
+    pub fn record(&self) -> Option<Term> {
+        child(&self.0)
+    }
+
+    pub fn field(&self) -> Option<Name> {
+        child(&self.0)
+    }
+}
+
+impl BinaryOp {
+    pub fn lhs(&self) -> Option<Term> {
+        nth_child(&self.0, 0)
+    }
+
+    pub fn operator(&self) -> Option<Punct> {
+        punct(&self.0)
+    }
+
+    pub fn rhs(&self) -> Option<Term> {
+        nth_child(&self.0, 1)
+    }
+}
+
+impl UnaryOp {
+    pub fn operator(&self) -> Option<Punct> {
+        punct(&self.0)
+    }
+
+    pub fn operand(&self) -> Option<Term> {
+        child(&self.0)
+    }
+}
+
+impl IfValue {
+    pub fn condition(&self) -> Option<Term> {
+        self.0
+            .find(BareSyntaxKind::IfCondition)
+            .and_then(|condition| child(&condition))
+    }
+
+    pub fn then_branch(&self) -> Option<IfBranch> {
+        self.0.find(BareSyntaxKind::IfThen).and_then(IfBranch::cast)
+    }
+
+    pub fn else_branch(&self) -> Option<IfBranch> {
+        self.0.find(BareSyntaxKind::IfElse).and_then(IfBranch::cast)
+    }
+
+    /// The result type, given either as an argument or as a type annotation
+    pub fn motive(&self) -> Option<Term> {
+        let motive = self.0.find(BareSyntaxKind::IfMotive)?;
+
+        child(&motive).or_else(|| child::<TypeAnnotation>(&motive)?.ty())
+    }
+}
+
+impl IfBranch {
+    pub fn term(&self) -> Option<Term> {
+        child(&self.0)
+    }
+
+    pub fn block(&self) -> Option<Block> {
+        child(&self.0)
+    }
+}
+
+impl Intrinsic {
+    pub fn arg_list(&self) -> Option<ArgList> {
+        child(&self.0)
+    }
+}
+
+impl Match {
+    pub fn scrutinee(&self) -> Option<Term> {
+        child(&self.0)
+    }
+
+    pub fn motive(&self) -> Option<MatchMotive> {
+        child(&self.0)
+    }
+
+    pub fn arms(&self) -> impl Iterator<Item = MatchArm> + use<> {
+        self.0
+            .find(BareSyntaxKind::MatchBlock)
+            .into_iter()
+            .flat_map(|block| children(&block))
+    }
+
+    /// The eliminator that the match elaborates into, as determined by its first
+    /// arm that matches on a constructor. Matches that only bind are `None`.
+    pub fn eliminator(&self) -> Option<Eliminator> {
+        self.arms()
+            .find_map(|arm| arm.pattern().and_then(|pattern| pattern.eliminator()))
+    }
+}
+
+impl MatchMotive {
+    pub fn binder(&self) -> Option<Name> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Option<Term> {
+        child(&self.0)
+    }
+}
+
+impl MatchArm {
+    pub fn pattern(&self) -> Option<Pattern> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Option<Term> {
+        child(&self.0)
+    }
+}
+
+/// What a `Pattern` matches on
+#[derive(Clone, Debug)]
+pub enum PatternKind {
+    True,
+    False,
+    Void,
+    Integer(Arc<BigInt>),
+    Wildcard,
+    Binding(Name),
+    /// `@nat_succ(n)`, where the predecessor is matched by the inner pattern
+    NatSucc(Option<Pattern>),
+    /// `@variant(name, ...)`, with one pattern for each field of the payload
+    Variant {
+        name: Option<Name>,
+        fields: Vec<Pattern>,
+    },
+}
+
+/// The intrinsic that a `@match` elaborates into
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Eliminator {
+    BoolElim,
+    NatElim,
+    VariantElim,
+}
+
+impl Pattern {
+    /// Gives `None` if the pattern failed to parse
+    pub fn kind(&self) -> Option<PatternKind> {
+        self.0
+            .children()
+            .find_map(|child| match child.bare().kind() {
+                BareSyntaxKind::TrueValue => Some(PatternKind::True),
+                BareSyntaxKind::FalseValue => Some(PatternKind::False),
+                BareSyntaxKind::VoidValue => Some(PatternKind::Void),
+                BareSyntaxKind::Integer(value) => Some(PatternKind::Integer(value.clone())),
+                BareSyntaxKind::Wildcard => Some(PatternKind::Wildcard),
+                BareSyntaxKind::Name => Name::cast(child).map(PatternKind::Binding),
+                BareSyntaxKind::NatSucc => Some(PatternKind::NatSucc(self::child(&child))),
+                BareSyntaxKind::VariantValue => Some(PatternKind::Variant {
+                    name: self::child(&child),
+                    fields: children(&child).collect(),
+                }),
+                _ => None,
+            })
+    }
+
+    pub fn binds(&self, name: &str) -> bool {
+        match self.kind() {
+            Some(PatternKind::Binding(binding)) => binding.text().as_deref() == Some(name),
+            Some(PatternKind::NatSucc(Some(inner))) => inner.binds(name),
+            Some(PatternKind::Variant { fields, .. }) => {
+                fields.iter().any(|field| field.binds(name))
+            }
+            _ => false,
+        }
+    }
+
+    pub fn eliminator(&self) -> Option<Eliminator> {
+        match self.kind()? {
+            PatternKind::True | PatternKind::False => Some(Eliminator::BoolElim),
+            PatternKind::Integer(_) | PatternKind::NatSucc(_) => Some(Eliminator::NatElim),
+            PatternKind::Variant { .. } => Some(Eliminator::VariantElim),
+            PatternKind::Void | PatternKind::Wildcard | PatternKind::Binding(_) => None,
+        }
+    }
+}
+
+impl RecordType {
+    pub fn field_defs(&self) -> impl Iterator<Item = FieldDef> + use<> {
+        self.0
+            .find(BareSyntaxKind::FieldDefList)
+            .into_iter()
+            .flat_map(|list| children(&list))
+    }
+}
+
+impl FieldDef {
+    pub fn names(&self) -> impl Iterator<Item = Name> + use<> {
+        children(&self.0)
+    }
+
+    pub fn type_annotation(&self) -> Option<TypeAnnotation> {
+        child(&self.0)
+    }
+}
+
+impl RecordValue {
+    /// The positional fields, which are `Term`s, and named fields, which are `FieldInit`s
+    pub fn fields(&self) -> impl Iterator<Item = Arc<SyntaxNode>> + use<> {
+        child::<ArgList>(&self.0)
+            .into_iter()
+            .flat_map(|arg_list| arg_list.0.children().collect::<Vec<_>>())
+            .filter(|field| {
+                Term::can_cast(field.bare().kind()) || FieldInit::can_cast(field.bare().kind())
+            })
+    }
+}
+
+impl FieldInit {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.0)
+    }
+
+    pub fn value(&self) -> Option<Term> {
+        child(&self.0)
+    }
+}
+
+impl VariantType {
+    pub fn variants(&self) -> impl Iterator<Item = VariantDef> + use<> {
+        self.0
+            .find(BareSyntaxKind::VariantDefList)
+            .into_iter()
+            .flat_map(|list| children(&list))
+    }
+}
+
+impl VariantDef {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.0)
+    }
+
+    pub fn payload(&self) -> Option<ArgList> {
+        child(&self.0)
+    }
+}
+
+impl VariantValue {
+    pub fn name(&self) -> Option<Name> {
+        child(&self.0)
+    }
+
+    pub fn arg_list(&self) -> Option<ArgList> {
+        child(&self.0)
+    }
+}
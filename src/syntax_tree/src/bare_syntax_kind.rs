@@ -1,3 +1,4 @@
+use crate::FloatValue;
 use derive_more::{IsVariant, PartialEq};
 use num_bigint::BigInt;
 use serde::{Deserialize, Serialize};
@@ -8,7 +9,9 @@ use util_text::{ColumnSpacingAtom, LineSpacingAtom};
 #[derive(Clone, Debug, Serialize, Deserialize, IsVariant, PartialEq, Eq)]
 pub enum BareSyntaxKind {
     Root,
-    Error { description: String },
+    Error {
+        description: String,
+    },
     ColumnSpacing(ColumnSpacingAtom),
     LineSpacing(LineSpacingAtom),
     Punct(Punct),
@@ -24,31 +27,61 @@ pub enum BareSyntaxKind {
     ArgList(Reparsable),
     FieldDef,
     FieldDefList,
+    VariantDef,
+    VariantDefList,
     TypeAnnotation,
     SinglelineComment(Box<str>),
     MultilineComment(Box<str>),
+    /// The documentation text of a `///` or `/** */` comment
+    DocComment(Box<str>),
     BuiltinType(BuiltinType),
     TrueValue,
     FalseValue,
     VoidValue,
     Integer(Arc<BigInt>),
+    Float(FloatValue),
+    String(Arc<str>),
+    CString(Arc<str>),
+    ByteString(Arc<[u8]>),
+    Character(char),
+    StringFragment,
     FnValue,
     IfValue,
+    IfCondition,
+    IfThen,
+    IfElse,
+    /// The result type of an `@if`, given as an argument or as a type annotation
+    IfMotive,
     RecordValue,
+    /// A named field of a record value, like `x: 1`
+    FieldInit,
+    VariantValue,
     Block,
     Variable(Arc<str>),
+    QualifiedVariable {
+        namespace: Arc<str>,
+        name: Option<Arc<str>>,
+    },
     Eval,
     ParenthesizedTerm,
     Call,
+    BinaryOp,
+    UnaryOp,
     Let,
     Nth,
+    FieldAccess,
     Match,
     MatchBlock,
     MatchArm,
+    /// The `@so` clause of a match, giving the result type in terms of the matched value
+    MatchMotive,
     Pattern,
+    /// The `_` pattern, which matches anything without binding it
+    Wildcard,
     BoolElim,
     NatElim,
     NatSucc,
+    Import,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, IsVariant, PartialEq, Eq)]
@@ -64,5 +97,6 @@ pub enum BuiltinType {
     Type,
     Fn,
     Record,
+    Variant,
     Nat,
 }
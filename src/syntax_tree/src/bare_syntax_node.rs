@@ -78,10 +78,27 @@ impl BareSyntaxNode {
         }
     }
 
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
     pub fn kind(&self) -> &BareSyntaxKind {
         &self.kind
     }
 
+    /// The documentation from the doc comments directly inside of this node
+    pub fn doc_comment(&self) -> Option<String> {
+        let docs = self
+            .children()
+            .filter_map(|child| match child.kind() {
+                BareSyntaxKind::DocComment(doc) => Some(doc.as_ref()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        (!docs.is_empty()).then(|| docs.join("\n"))
+    }
+
     pub fn children(&self) -> impl Iterator<Item = &Arc<BareSyntaxNode>> {
         self.children.iter()
     }
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+/// A floating-point literal, compared by its bits so that syntax kinds can be `Eq`
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct FloatValue(pub f64);
+
+impl PartialEq for FloatValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for FloatValue {}
+
+impl Debug for FloatValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
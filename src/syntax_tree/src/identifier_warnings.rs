@@ -0,0 +1,95 @@
+use crate::{BareSyntaxKind, SyntaxNode};
+use std::{collections::HashMap, fmt::Display, sync::Arc};
+use text_edit::TextPointRangeUtf16;
+use unicode_security::{MixedScript, skeleton};
+
+/// An identifier that is allowed, but is likely to mislead readers
+#[derive(Clone, Debug)]
+pub struct IdentifierWarning {
+    pub range: TextPointRangeUtf16,
+    pub name: Arc<str>,
+    pub kind: IdentifierWarningKind,
+}
+
+#[derive(Clone, Debug)]
+pub enum IdentifierWarningKind {
+    MixedScript,
+    /// Looks the same as the other identifier
+    Confusable(Arc<str>),
+}
+
+impl Display for IdentifierWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = &self.name;
+
+        match &self.kind {
+            IdentifierWarningKind::MixedScript => {
+                write!(f, "Identifier `{name}` mixes characters from different scripts")
+            }
+            IdentifierWarningKind::Confusable(other) => {
+                write!(f, "Identifier `{name}` can be confused with `{other}`")
+            }
+        }
+    }
+}
+
+impl SyntaxNode {
+    /// Finds identifiers that mix characters from different scripts, or that look
+    /// the same as a different identifier in the same tree
+    pub fn identifier_warnings(self: &Arc<Self>) -> Vec<IdentifierWarning> {
+        let identifiers = self
+            .descendants()
+            .filter_map(|node| match node.bare().kind() {
+                BareSyntaxKind::Identifier(name) | BareSyntaxKind::Variable(name) => {
+                    Some((name.clone(), node))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        // Identifiers are confusable when they have the same skeleton (see UTS #39)
+        let mut skeletons = HashMap::<Arc<str>, String>::new();
+        let mut by_skeleton = HashMap::<String, Vec<Arc<str>>>::new();
+
+        for (name, _) in identifiers.iter() {
+            if skeletons.contains_key(name) {
+                continue;
+            }
+
+            let name_skeleton = skeleton(name).collect::<String>();
+            by_skeleton
+                .entry(name_skeleton.clone())
+                .or_default()
+                .push(name.clone());
+            skeletons.insert(name.clone(), name_skeleton);
+        }
+
+        let mut warnings = vec![];
+
+        for (name, node) in identifiers.iter() {
+            if !name.is_single_script() {
+                warnings.push(IdentifierWarning {
+                    range: node.text_range(),
+                    name: name.clone(),
+                    kind: IdentifierWarningKind::MixedScript,
+                });
+            }
+
+            // Only names involving non-ASCII characters are reported, since
+            // plain ASCII names such as `rn` and `m` are visibly different in code
+            let confusable = by_skeleton[&skeletons[name]]
+                .iter()
+                .find(|other| *other != name && !(name.is_ascii() && other.is_ascii()));
+
+            if let Some(other) = confusable {
+                warnings.push(IdentifierWarning {
+                    range: node.text_range(),
+                    name: name.clone(),
+                    kind: IdentifierWarningKind::Confusable(other.clone()),
+                });
+            }
+        }
+
+        warnings
+    }
+}
@@ -1,7 +1,12 @@
+pub mod ast;
 mod bare_syntax_kind;
 mod bare_syntax_node;
+mod float_value;
+mod identifier_warnings;
 mod syntax_node;
 
 pub use bare_syntax_kind::*;
 pub use bare_syntax_node::*;
+pub use float_value::FloatValue;
+pub use identifier_warnings::{IdentifierWarning, IdentifierWarningKind};
 pub use syntax_node::*;
@@ -1,5 +1,7 @@
-use crate::{BareSyntaxKind, BareSyntaxNode};
-use derive_more::From;
+use crate::{
+    BareSyntaxKind, BareSyntaxNode, BuiltinType,
+    ast::{self, AstNode},
+};
 use num_bigint::BigInt;
 use std::{fmt::Debug, sync::Arc};
 use text_edit::{TextPointRangeUtf16, TextPointUtf16};
@@ -75,13 +77,14 @@ impl SyntaxNode {
         Ok(())
     }
 
-    pub fn bindings(self: &Arc<Self>) -> impl Iterator<Item = Binding> {
-        self.children()
-            .filter(|child| matches!(child.bare.kind, BareSyntaxKind::Binding))
-            .map(|binding| Binding {
-                name: binding.find_name(),
-                value: binding.find_term(),
-            })
+    pub fn find_string(self: &Arc<Self>) -> Option<Arc<str>> {
+        self.children().find_map(|child| {
+            if let BareSyntaxKind::String(value) = child.bare.kind() {
+                Some(value.clone())
+            } else {
+                None
+            }
+        })
     }
 
     pub fn find_name(self: &Arc<Self>) -> Option<Arc<str>> {
@@ -118,50 +121,6 @@ impl SyntaxNode {
         })
     }
 
-    pub fn find_names(self: &Arc<Self>) -> impl Iterator<Item = Arc<str>> {
-        self.children()
-            .filter(|child| matches!(child.bare.kind(), BareSyntaxKind::Name))
-            .flat_map(|name| {
-                name.children().find_map(|child| {
-                    if let BareSyntaxKind::Identifier(name) = child.bare.kind() {
-                        Some(name.clone())
-                    } else {
-                        None
-                    }
-                })
-            })
-    }
-
-    pub fn find_param_heads(self: &Arc<Self>) -> impl Iterator<Item = ParamHead> {
-        self.children()
-            .filter(|child| matches!(child.bare.kind(), BareSyntaxKind::ParamHead))
-            .flat_map(|param_head| {
-                let mut name = param_head.find_name();
-                let mut implicit_name = param_head.find_implicit_name();
-                let mut implicitness = Implicitness::Explicit.into();
-
-                if name.is_none() {
-                    if let Some(implicit_name) = implicit_name.take() {
-                        name = Some(implicit_name);
-                        implicitness = Implicitness::Implicit.into();
-                    }
-                }
-
-                if let Some(implicit_name) = implicit_name {
-                    implicitness = NamedImplicitness::ImplicitWithName(implicit_name);
-                }
-
-                Some(ParamHead { name, implicitness })
-            })
-    }
-
-    pub fn find_eval(self: &Arc<Self>) -> Option<Eval> {
-        self.find(BareSyntaxKind::Eval).map(|eval| {
-            let value = eval.find_term();
-            Eval { value }
-        })
-    }
-
     pub fn find_term(self: &Arc<Self>) -> Option<Arc<Self>> {
         self.find(BareSyntaxKind::Term)
     }
@@ -180,116 +139,75 @@ impl SyntaxNode {
         })
     }
 
-    pub fn find_fn(self: &Arc<Self>) -> Option<Func> {
-        self.find(BareSyntaxKind::FnValue).map(|func| {
-            let param_list = func.find(BareSyntaxKind::ParamList);
-            let return_type_annotation = func.find(BareSyntaxKind::TypeAnnotation);
-            let block = func.find(BareSyntaxKind::Block);
+    pub fn descendants(self: &Arc<Self>) -> impl Iterator<Item = Arc<Self>> {
+        let mut stack = Vec::from_iter(self.children());
 
-            Func {
-                param_list,
-                return_type_annotation,
-                block,
-            }
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.children());
+            Some(node)
         })
     }
 
-    pub fn param_list_params(self: &Arc<Self>) -> impl Iterator<Item = Param> {
-        self.children()
-            .filter(|param| matches!(param.bare.kind(), BareSyntaxKind::Param))
-            .map(|param| {
-                let type_annotation = param.find(BareSyntaxKind::TypeAnnotation);
-
-                Param {
-                    param,
-                    type_annotation,
+    pub fn free_variables(self: &Arc<Self>) -> impl Iterator<Item = (Arc<str>, Arc<Self>)> {
+        self.descendants()
+            .filter_map(|node| match node.bare.kind() {
+                BareSyntaxKind::Variable(name) if !node.is_bound_locally(name) => {
+                    Some((name.clone(), node))
                 }
+                _ => None,
             })
     }
-}
-
-#[derive(Clone, Debug)]
-pub struct Binding {
-    pub name: Option<Arc<str>>,
-    pub value: Option<Arc<SyntaxNode>>,
-}
 
-#[derive(Clone, Debug)]
-pub struct Eval {
-    pub value: Option<Arc<SyntaxNode>>,
-}
-
-#[derive(Clone, Debug)]
-pub struct Func {
-    pub param_list: Option<Arc<SyntaxNode>>,
-    pub return_type_annotation: Option<Arc<SyntaxNode>>,
-    pub block: Option<Arc<SyntaxNode>>,
-}
-
-impl Func {
-    pub fn params(&self) -> impl Iterator<Item = Param> {
-        self.param_list
-            .as_ref()
-            .map(|list| list.param_list_params())
-            .into_iter()
-            .flatten()
+    pub fn qualified_variables(
+        self: &Arc<Self>,
+    ) -> impl Iterator<Item = (Arc<str>, Arc<str>, Arc<Self>)> {
+        self.descendants()
+            .filter_map(|node| match node.bare.kind() {
+                BareSyntaxKind::QualifiedVariable {
+                    namespace,
+                    name: Some(name),
+                } => Some((namespace.clone(), name.clone(), node)),
+                _ => None,
+            })
     }
 
-    pub fn body(&self) -> impl Iterator<Item = Arc<SyntaxNode>> {
-        self.block.as_ref().into_iter().flat_map(|block| {
-            block
-                .children()
-                .filter(|child| matches!(child.bare.kind, BareSyntaxKind::Term))
-        })
-    }
-}
+    pub fn is_bound_locally(&self, name: &str) -> bool {
+        let mut ancestor = self.parent();
+
+        while let Some(node) = ancestor {
+            let binds_name = match node.bare.kind() {
+                BareSyntaxKind::FnValue => ast::FnValue::cast(node.clone())
+                    .and_then(|func| func.param_list())
+                    .is_some_and(|list| binds_param(&list, name)),
+                BareSyntaxKind::BuiltinType(BuiltinType::Fn) => ast::FnType::cast(node.clone())
+                    .and_then(|func| func.param_list())
+                    .is_some_and(|list| binds_param(&list, name)),
+                BareSyntaxKind::Let => ast::Let::cast(node.clone())
+                    .and_then(|let_| let_.binder())
+                    .is_some_and(|binder| binder.as_ref() == name),
+                BareSyntaxKind::MatchArm => ast::MatchArm::cast(node.clone())
+                    .and_then(|arm| arm.pattern())
+                    .is_some_and(|pattern| pattern.binds(name)),
+                BareSyntaxKind::MatchMotive => node
+                    .find_name()
+                    .is_some_and(|binder| binder.as_ref() == name),
+                _ => false,
+            };
+
+            if binds_name {
+                return true;
+            }
 
-#[derive(Clone, Debug)]
-pub struct Param {
-    pub param: Arc<SyntaxNode>,
-    pub type_annotation: Option<Arc<SyntaxNode>>,
-}
+            ancestor = node.parent();
+        }
 
-impl Param {
-    pub fn param_heads(&self) -> impl Iterator<Item = ParamHead> {
-        self.param.find_param_heads()
+        false
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct ParamHead {
-    pub name: Option<Arc<str>>,
-    pub implicitness: NamedImplicitness,
-}
-
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub enum Implicitness {
-    Explicit,
-    Implicit,
-}
-
-#[derive(Clone, Debug, From)]
-pub enum NamedImplicitness {
-    ImplicitWithName(Arc<str>),
-    Implicitness(Implicitness),
-}
-
-impl NamedImplicitness {
-    pub fn matches_param(&self, param_name: &str, param_implicitness: Implicitness) -> bool {
-        match self {
-            NamedImplicitness::ImplicitWithName(name) => {
-                param_name == name.as_ref() && param_implicitness == Implicitness::Implicit
-            }
-            NamedImplicitness::Implicitness(lambda_implicitness) => {
-                param_implicitness == *lambda_implicitness
-            }
-        }
-    }
-
-    pub fn to_implicitness(&self) -> Implicitness {
-        match self {
-            NamedImplicitness::ImplicitWithName(_) => Implicitness::Implicit,
-            NamedImplicitness::Implicitness(implicitness) => *implicitness,
-        }
-    }
+fn binds_param(list: &ast::ParamList, name: &str) -> bool {
+    list.params()
+        .flat_map(|param| param.heads())
+        .any(|head| head.binder().as_deref() == Some(name))
 }
@@ -1,4 +1,5 @@
 mod line_index;
+mod source_position;
 mod text_edit_utf16;
 mod text_length_utf16;
 mod text_point_diff_utf16;
@@ -7,6 +8,7 @@ mod text_range_utf16;
 
 pub use line_index::*;
 use serde::{Deserialize, Serialize};
+pub use source_position::SourcePosition;
 use std::sync::Arc;
 pub use text_edit_utf16::*;
 pub use text_length_utf16::*;
@@ -0,0 +1,38 @@
+use crate::{LineIndex, TextLengthUtf16, TextPointUtf16};
+use serde::{Deserialize, Serialize};
+
+/// A position within source text, both as a byte offset and as a UTF-16 point
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourcePosition {
+    pub byte: usize,
+    pub point: TextPointUtf16,
+}
+
+impl SourcePosition {
+    pub const fn start() -> Self {
+        Self {
+            byte: 0,
+            point: TextPointUtf16::start(),
+        }
+    }
+
+    /// The position immediately after the character `c` at this position
+    pub fn after(&self, c: char) -> Self {
+        let point = if c == '\n' {
+            TextPointUtf16 {
+                line: LineIndex(self.point.line.0 + 1),
+                col: TextLengthUtf16(0),
+            }
+        } else {
+            TextPointUtf16 {
+                line: self.point.line,
+                col: TextLengthUtf16(self.point.col.0 + c.len_utf16()),
+            }
+        };
+
+        Self {
+            byte: self.byte + c.len_utf8(),
+            point,
+        }
+    }
+}
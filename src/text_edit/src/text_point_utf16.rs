@@ -2,6 +2,7 @@ use crate::{LineIndex, TextLengthUtf16, TextPointDiffUtf16};
 use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
+    fmt::Display,
     ops::{Add, AddAssign},
 };
 
@@ -39,6 +40,12 @@ impl TextPointUtf16 {
     }
 }
 
+impl Display for TextPointUtf16 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line.0, self.col.0)
+    }
+}
+
 impl Ord for TextPointUtf16 {
     fn cmp(&self, other: &Self) -> Ordering {
         self.line
@@ -37,7 +37,7 @@ impl TextPointRangeUtf16 {
 
 impl Display for TextPointRangeUtf16 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}..{:?}", self.start(), self.end())
+        write!(f, "{}..{}", self.start(), self.end())
     }
 }
 
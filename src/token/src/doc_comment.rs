@@ -0,0 +1,34 @@
+/// Whether a comment, including its delimiters, documents the binding that follows it.
+/// Like `////` and `/***`, comments with extra delimiter characters are ordinary comments.
+pub fn is_doc_comment(comment: &str) -> bool {
+    (comment.starts_with("///") && !comment.starts_with("////"))
+        || (comment.starts_with("/**") && !comment.starts_with("/***") && comment != "/**/")
+}
+
+/// The documentation written in a doc comment, without its delimiters
+/// or the `*` that conventionally starts each line of a `/** */` comment
+pub fn doc_comment_text(comment: &str) -> String {
+    if let Some(line) = comment.strip_prefix("///") {
+        return line.strip_prefix(' ').unwrap_or(line).trim_end().into();
+    }
+
+    let content = comment.strip_prefix("/**").unwrap_or(comment);
+    let content = content.strip_suffix("*/").unwrap_or(content);
+
+    let lines = content
+        .lines()
+        .map(|line| {
+            let line = line.trim_start();
+            let line = line.strip_prefix('*').unwrap_or(line);
+            line.strip_prefix(' ').unwrap_or(line).trim_end()
+        })
+        .collect::<Vec<_>>();
+
+    let start = lines.iter().position(|line| !line.is_empty());
+    let end = lines.iter().rposition(|line| !line.is_empty());
+
+    match start.zip(end) {
+        Some((start, end)) => lines[start..=end].join("\n"),
+        None => String::new(),
+    }
+}
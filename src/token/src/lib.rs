@@ -1,19 +1,23 @@
 mod directive;
+mod doc_comment;
+mod number;
 mod punct;
 mod string;
 
 use derivative::Derivative;
 use derive_more::{Deref, IsVariant, PartialEq, Unwrap};
 pub use directive::Directive;
+pub use doc_comment::{doc_comment_text, is_doc_comment};
 use lazy_static::lazy_static;
 use num_bigint::BigInt;
+pub use number::{NumberError, Radix};
 pub use punct::Punct;
 use serde::{Deserialize, Serialize};
 use std::{
     fmt::{Debug, Display},
     sync::Arc,
 };
-pub use string::{StringLiteral, StringModifier};
+pub use string::{StringError, StringErrorKind, StringLiteral, StringModifier};
 use util_infinite_iterator::IsEnd;
 use util_text::{ColumnSpacingAtom, LineSpacingAtom};
 
@@ -25,13 +29,27 @@ pub struct Token<S> {
 
     #[derivative(PartialEq = "ignore")]
     pub source: S,
+
+    /// Where the token ends, which is also where the next token starts
+    #[derivative(PartialEq = "ignore")]
+    pub end: S,
 }
 
-impl<S> Token<S> {
+impl<S: Copy> Token<S> {
     pub fn new(kind: TokenKind, source: S) -> Self {
-        Token { kind, source }
+        Token {
+            kind,
+            source,
+            end: source,
+        }
     }
 
+    pub fn span(&self) -> (S, S) {
+        (self.source, self.end)
+    }
+}
+
+impl<S> Token<S> {
     pub fn is_end_of_file(&self) -> bool {
         self.kind.is_end_of_file()
     }
@@ -55,15 +73,17 @@ pub enum TokenKind {
     LineSpacing(LineSpacingAtom),
     Identifier(String),
     Polymorph(String),
-    String(StringLiteral),
+    String(Box<StringLiteral>),
     MissingStringTermination,
     Integer(Arc<BigInt>, String),
-    Float(f64, usize),
+    Float(f64, String),
+    MalformedNumber(String, NumberError),
     Directive(Directive),
     Punct(Punct),
     Label(String),
     SinglelineComment(String),
     MultilineComment(String, IsTerminated),
+    DocComment(String, IsTerminated),
 }
 
 #[derive(Clone, Debug, PartialEq, IsVariant, Unwrap, Serialize, Deserialize)]
@@ -100,11 +120,13 @@ impl Display for TokenKind {
             Self::Polymorph(name) => write!(f, "${}", name),
             Self::String(string_literal) => write!(f, "{}", string_literal),
             Self::MissingStringTermination => Ok(()),
-            Self::Integer(value, _) => write!(f, "{}", value),
-            Self::Float(value, _) => write!(f, "{}", value),
+            Self::Integer(_, text) => write!(f, "{}", text),
+            Self::Float(_, text) => write!(f, "{}", text),
+            Self::MalformedNumber(text, _) => write!(f, "{}", text),
             Self::Directive(directive) => write!(f, "{}", directive),
             Self::SinglelineComment(content) => write!(f, "{}", content),
             Self::MultilineComment(content, _) => write!(f, "{}", content),
+            Self::DocComment(content, _) => write!(f, "{}", content),
             Self::Punct(punct) => write!(f, "{}", punct),
             Self::Label(name) => write!(f, "@{}@", name),
         }
@@ -119,7 +141,7 @@ const ASSIGNMENT_OPERATORS: &[&'static str] = &[
 const NON_ASSIGNMENT_OPERATORS: &[&'static str] = &[
     ",", ".", ":", "::", "(", ")", "[", "]", "{", "}", "$", "++", "--", "!", "~", "*", "/", "%",
     "+", "-", "<<", "<<<", ">>", ">>>", "<", "<=", ">", ">=", "==", "!=", "&", "^", "|", "&&",
-    "||",
+    "||", "=>",
 ];
 
 pub const ALL_DIRECTIVES: &[&'static str] = &[
@@ -131,7 +153,9 @@ pub const ALL_DIRECTIVES: &[&'static str] = &[
     "Variant",
     "variant",
     "eval",
+    "import",
     "match",
+    "so",
     "bool_elim",
     "nat_elim",
     "nat_succ",
@@ -178,11 +202,13 @@ impl TokenKind {
             | Self::MissingStringTermination
             | Self::Integer { .. }
             | Self::Float { .. }
+            | Self::MalformedNumber(..)
             | Self::Directive(_)
             | Self::Punct(_)
             | Self::Label(_)
             | Self::SinglelineComment(..)
-            | Self::MultilineComment(..) => 0,
+            | Self::MultilineComment(..)
+            | Self::DocComment(..) => 0,
         }
     }
 
@@ -197,12 +223,14 @@ impl TokenKind {
             TokenKind::String(string) => string.literal.len(),
             TokenKind::MissingStringTermination => 0,
             TokenKind::Integer(_, text) => text.len(),
-            TokenKind::Float(_, len) => *len,
+            TokenKind::Float(_, text) => text.len(),
+            TokenKind::MalformedNumber(text, _) => text.len(),
             TokenKind::Directive(directive) => directive.len_with_prefix(),
             TokenKind::Punct(punct) => punct.len(),
             TokenKind::SinglelineComment(content) => content.len(),
             TokenKind::MultilineComment(content, _) => content.len(),
-            TokenKind::Label(_) => todo!(),
+            TokenKind::DocComment(content, _) => content.len(),
+            TokenKind::Label(name) => 2 + name.len(),
         }
     }
 
@@ -213,7 +241,7 @@ impl TokenKind {
         }
     }
 
-    pub fn at<S>(self, source: S) -> Token<S> {
-        Token { kind: self, source }
+    pub fn at<S: Copy>(self, source: S) -> Token<S> {
+        Token::new(self, source)
     }
 }
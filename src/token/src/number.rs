@@ -0,0 +1,70 @@
+use std::fmt::Display;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Radix {
+    Binary,
+    Octal,
+    Decimal,
+    Hexadecimal,
+}
+
+impl Radix {
+    pub fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix {
+            "0b" => Some(Self::Binary),
+            "0o" => Some(Self::Octal),
+            "0x" => Some(Self::Hexadecimal),
+            _ => None,
+        }
+    }
+
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Radix::Binary => "0b",
+            Radix::Octal => "0o",
+            Radix::Decimal => "",
+            Radix::Hexadecimal => "0x",
+        }
+    }
+
+    pub fn value(&self) -> u32 {
+        match self {
+            Radix::Binary => 2,
+            Radix::Octal => 8,
+            Radix::Decimal => 10,
+            Radix::Hexadecimal => 16,
+        }
+    }
+}
+
+impl Display for Radix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Radix::Binary => write!(f, "binary"),
+            Radix::Octal => write!(f, "octal"),
+            Radix::Decimal => write!(f, "decimal"),
+            Radix::Hexadecimal => write!(f, "hexadecimal"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum NumberError {
+    MissingDigits(Radix),
+    InvalidDigit(char, Radix),
+    MissingExponentDigits,
+}
+
+impl Display for NumberError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumberError::MissingDigits(radix) => {
+                write!(f, "Expected {} digits after `{}`", radix, radix.prefix())
+            }
+            NumberError::InvalidDigit(c, radix) => {
+                write!(f, "Invalid digit `{}` in {} literal", c, radix)
+            }
+            NumberError::MissingExponentDigits => write!(f, "Expected digits in exponent"),
+        }
+    }
+}
@@ -1,21 +1,23 @@
-use std::fmt::Display;
+use std::{fmt::Display, ops::Range};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct StringLiteral {
+    /// The literal as written, including any prefix and quotes
     pub literal: String,
+    pub modifier: StringModifier,
+    /// The decoded contents, with invalid escapes left out
+    pub value: Vec<u8>,
+    pub errors: Vec<StringError>,
 }
 
 impl StringLiteral {
     pub fn modifier(&self) -> StringModifier {
-        if self.literal.starts_with('"') {
-            return StringModifier::Normal;
-        }
-
-        if self.literal.starts_with('\'') {
-            return StringModifier::Character;
-        }
+        self.modifier
+    }
 
-        panic!("Invalid string literal")
+    /// The decoded contents as text, which is only unavailable for byte strings
+    pub fn as_str(&self) -> Option<&str> {
+        std::str::from_utf8(&self.value).ok()
     }
 }
 
@@ -23,6 +25,89 @@ impl StringLiteral {
 pub enum StringModifier {
     Normal,
     Character,
+    CString,
+    ByteString,
+    RawString,
+}
+
+impl StringModifier {
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            StringModifier::Normal | StringModifier::Character => "",
+            StringModifier::CString => "c",
+            StringModifier::ByteString => "b",
+            StringModifier::RawString => "r",
+        }
+    }
+
+    pub fn from_prefix(prefix: char) -> Option<Self> {
+        match prefix {
+            'c' => Some(Self::CString),
+            'b' => Some(Self::ByteString),
+            'r' => Some(Self::RawString),
+            _ => None,
+        }
+    }
+
+    pub fn close_char(&self) -> char {
+        match self {
+            StringModifier::Character => '\'',
+            _ => '"',
+        }
+    }
+
+    pub fn has_escapes(&self) -> bool {
+        !matches!(self, StringModifier::RawString)
+    }
+}
+
+/// A problem with part of a string literal, such as an invalid escape sequence
+#[derive(Clone, Debug, PartialEq)]
+pub struct StringError {
+    /// Byte range within the literal that the error applies to
+    pub range: Range<usize>,
+    pub kind: StringErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StringErrorKind {
+    UnknownEscape(char),
+    MissingEscape,
+    InvalidHexEscape,
+    HexEscapeOutOfRange,
+    InvalidUnicodeEscape,
+    UnicodeEscapeInByteString,
+    NonAsciiInByteString,
+    NulInCString,
+    NotOneCharacter,
+}
+
+impl Display for StringErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownEscape(c) => write!(f, "Unknown escape sequence `\\{}`", c),
+            Self::MissingEscape => write!(f, "Expected escape sequence after `\\`"),
+            Self::InvalidHexEscape => {
+                write!(f, "Expected two hexadecimal digits after `\\x`")
+            }
+            Self::HexEscapeOutOfRange => {
+                write!(f, "Escape is out of range, expected at most `\\x7F`")
+            }
+            Self::InvalidUnicodeEscape => {
+                write!(f, "Expected unicode escape of the form `\\u{{1F600}}`")
+            }
+            Self::UnicodeEscapeInByteString => {
+                write!(f, "Unicode escapes are not allowed in byte strings")
+            }
+            Self::NonAsciiInByteString => {
+                write!(f, "Byte strings can only contain ASCII characters")
+            }
+            Self::NulInCString => write!(f, "C strings cannot contain nul characters"),
+            Self::NotOneCharacter => {
+                write!(f, "Character literals must contain exactly one character")
+            }
+        }
+    }
 }
 
 impl Display for StringLiteral {
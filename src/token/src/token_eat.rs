@@ -29,7 +29,7 @@ impl<S, I: InfinitePeekable<Token<S>>> TokenEat<S> for I {
     fn eat_string(&mut self) -> Option<StringLiteral> {
         self.peek()
             .is_string()
-            .then(|| self.next().kind.unwrap_string())
+            .then(|| *self.next().kind.unwrap_string())
     }
 
     fn eat_integer(&mut self) -> Option<BigInt> {
@@ -50,7 +50,7 @@ impl<S: Copy> Character<S> {
     #[inline]
     pub fn is_identifier_start(&self) -> bool {
         match self {
-            Character::At(c, _) => c.is_ascii_alphabetic() || *c == '_',
+            Character::At(c, _) => *c == '_' || unicode_ident::is_xid_start(*c),
             Character::End(_) => false,
         }
     }
@@ -58,7 +58,7 @@ impl<S: Copy> Character<S> {
     #[inline]
     pub fn is_identifier_continue(&self) -> bool {
         match self {
-            Character::At(c, _) => c.is_ascii_alphabetic() || c.is_ascii_digit() || *c == '_',
+            Character::At(c, _) => unicode_ident::is_xid_continue(*c),
             Character::End(_) => false,
         }
     }
@@ -3,12 +3,14 @@ mod column_spacing_atom;
 mod eatable;
 mod line_spacing_atom;
 mod peeker;
+mod positioned;
 
 pub use character::Character;
 pub use column_spacing_atom::ColumnSpacingAtom;
 pub use eatable::Eatable;
 pub use line_spacing_atom::LineSpacingAtom;
 pub use peeker::Peeker as CharacterPeeker;
+pub use positioned::Positioned;
 use util_infinite_iterator::InfiniteIterator;
 
 pub trait Lexable<S: Copy>: InfiniteIterator<Item = Character<S>> {
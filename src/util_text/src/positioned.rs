@@ -0,0 +1,42 @@
+use crate::Character;
+use text_edit::SourcePosition;
+use util_infinite_iterator::InfiniteIterator;
+
+/// Tags each character with its position in the source text
+pub struct Positioned<I: Iterator<Item = char>> {
+    chars: I,
+    next: SourcePosition,
+}
+
+impl<I: Iterator<Item = char>> Positioned<I> {
+    pub fn new(chars: I) -> Self {
+        Self {
+            chars,
+            next: SourcePosition::start(),
+        }
+    }
+
+    /// Tags characters starting from `position`, for when only the rest of
+    /// the source text is being lexed
+    pub fn resume(chars: I, position: SourcePosition) -> Self {
+        Self {
+            chars,
+            next: position,
+        }
+    }
+}
+
+impl<I: Iterator<Item = char>> InfiniteIterator for Positioned<I> {
+    type Item = Character<SourcePosition>;
+
+    fn next(&mut self) -> Self::Item {
+        match self.chars.next() {
+            Some(c) => {
+                let at = self.next;
+                self.next = at.after(c);
+                Character::At(c, at)
+            }
+            None => Character::End(self.next),
+        }
+    }
+}